@@ -1,8 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use std::fmt::Debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use recollection::{
-    RecolResult, RecolError,
-    data::graph::{Direction, Node, Edge, Graph},
+    algo::{dijkstra, page_rank, PageRankOptions},
+    data::{csr::Csr, generators, graph::{Graph, NodeIx}},
 };
 
 fn graph<N, E>(directed: bool) -> Graph<N, E> where
@@ -23,16 +24,62 @@ fn add_nodes(n: usize) {
 
 fn add_nodes_edges(nodes: usize) {
     let mut g = graph::<usize, usize>(true);
-    let mut prev_nodes: Vec<usize> = vec![];
+    let mut prev_nodes: Vec<NodeIx> = vec![];
     for i in 0..nodes {
         let n = g.add(i);
         for (j, node) in (0..i).enumerate() {
-            let _edge = g.add_edge(n, node, i+j);
+            let _edge = g.add_edge(n, NodeIx::new(node), i+j);
         }
         prev_nodes.push(n);
     }
 }
 
+fn graph_sum_neighbors(g: &Graph<usize, usize>) -> usize {
+    (0..g.node_count())
+        .flat_map(|i| g.neighbors(NodeIx::new(i)))
+        .map(|n| n.index())
+        .sum()
+}
+
+fn csr_sum_neighbors(g: &Csr<usize, usize>) -> usize {
+    (0..g.node_count())
+        .flat_map(|i| g.neighbors(NodeIx::new(i)))
+        .map(|n| n.index())
+        .sum()
+}
+
+fn bench_csr(c: &mut Criterion) {
+    let g: Graph<usize, usize> = generators::erdos_renyi(10_000, 16.0 / 10_000.0, true, &mut StdRng::seed_from_u64(0));
+    let csr = Csr::from(&g);
+    let pairs = {
+        let mut rng = StdRng::seed_from_u64(1);
+        (0..1000)
+            .map(|_| (NodeIx::new(rng.gen_range(0..10_000)), NodeIx::new(rng.gen_range(0..10_000))))
+            .collect::<Vec<_>>()
+    };
+
+    c.bench_function("graph_neighbors 10k", |b| b.iter(|| graph_sum_neighbors(black_box(&g))));
+    c.bench_function("csr_neighbors 10k", |b| b.iter(|| csr_sum_neighbors(black_box(&csr))));
+
+    c.bench_function("graph_contains_edge 10k", |b| b.iter(|| {
+        pairs.iter().filter(|&&(x, y)| black_box(&g).contains_edge(x, y)).count()
+    }));
+    c.bench_function("csr_contains_edge 10k", |b| b.iter(|| {
+        pairs.iter().filter(|&&(x, y)| black_box(&csr).contains_edge(x, y)).count()
+    }));
+}
+
+fn bench_algo(c: &mut Criterion) {
+    let grid: Graph<(), ()> = generators::grid(100, 100, false);
+    let ba: Graph<(), ()> = generators::barabasi_albert(10_000, 3, &mut StdRng::seed_from_u64(0));
+
+    c.bench_function("dijkstra grid 100x100", |b| b.iter(|| {
+        dijkstra(black_box(&grid), NodeIx::new(0), None, |_| 1usize)
+    }));
+    c.bench_function("page_rank barabasi_albert 10k", |b| b.iter(|| {
+        page_rank(black_box(&ba), PageRankOptions::default())
+    }));
+}
 
 fn bench(c: &mut Criterion) {
     c.bench_function("graph_add_nodes 10", |b| b.iter(|| add_nodes(black_box(10))));
@@ -44,5 +91,5 @@ fn bench(c: &mut Criterion) {
     c.bench_function("graph_add_nodes_edges 40", |b| b.iter(|| add_nodes_edges(black_box(40))));
 }
 
-criterion_group!(benches, bench);
+criterion_group!(benches, bench, bench_csr, bench_algo);
 criterion_main!(benches);
@@ -0,0 +1,305 @@
+//! Measures of how central each node is to a graph. Every function
+//! returns one score per node, indexed by `NodeIx`.
+//!
+use std::collections::VecDeque;
+use crate::data::graph::{Direction, Graph, IndexType, NodeIx};
+
+/// Settings for `page_rank`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankOptions {
+    /// The chance of following an edge rather than jumping to a
+    /// random node
+    pub damping: f64,
+    /// Stop once the ranks move by less than this in total
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for PageRankOptions {
+    fn default() -> Self {
+        Self { damping: 0.85, tolerance: 1e-6, max_iterations: 100 }
+    }
+}
+
+/// The share of each node's rank passed along each of its out edges,
+/// or `None` for a dangling node with no out edges
+fn shares<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<Option<f64>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    g.node_indices()
+        .map(|n| match g.neighbors(n).count() {
+            0 => None,
+            d => Some(1.0 / d as f64),
+        })
+        .collect()
+}
+
+/// The new rank of node `v`: what reaches it over its in edges, plus
+/// `base` for random jumps and dangling nodes
+fn pull<N, E, Ix>(g: &Graph<N, E, Ix>, v: usize, rank: &[f64], shares: &[Option<f64>], base: f64, damping: f64) -> f64
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let incoming = g.neighbors_directed(NodeIx::new(v), Direction::Incoming)
+        .map(|u| rank[u.index()] * shares[u.index()].unwrap_or(0.0))
+        .sum::<f64>();
+    base + damping * incoming
+}
+
+/// PageRank: the long run chance of a random walker being at each
+/// node, if at every step it follows an out edge with probability
+/// `damping` and otherwise jumps to a node chosen uniformly. Walkers
+/// at dangling nodes always jump. Ranks sum to 1.
+pub fn page_rank<N, E, Ix>(g: &Graph<N, E, Ix>, options: PageRankOptions) -> Vec<f64>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    run_page_rank(g, options, |rank, shares, base| {
+        (0..g.node_count())
+            .map(|v| pull(g, v, rank, shares, base, options.damping))
+            .collect()
+    })
+}
+
+/// `page_rank`, with each iteration computed in parallel over the
+/// nodes
+#[cfg(feature = "rayon")]
+pub fn parallel_page_rank<N, E, Ix>(g: &Graph<N, E, Ix>, options: PageRankOptions) -> Vec<f64>
+where
+    N: Clone + Sync,
+    E: Clone + Sync,
+    Ix: IndexType + Send + Sync,
+{
+    use rayon::prelude::*;
+    run_page_rank(g, options, |rank, shares, base| {
+        g.nodes()
+            .par_iter()
+            .enumerate()
+            .map(|(v, _)| pull(g, v, rank, shares, base, options.damping))
+            .collect()
+    })
+}
+
+/// Iterate `step`, which computes every node's new rank given the
+/// old ranks, the shares and the base rank, until the ranks settle
+fn run_page_rank<N, E, Ix, F>(g: &Graph<N, E, Ix>, options: PageRankOptions, mut step: F) -> Vec<f64>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&[f64], &[Option<f64>], f64) -> Vec<f64>,
+{
+    let n = g.node_count();
+    if n == 0 {
+        return Vec::new();
+    }
+    let d = options.damping;
+    let shares = shares(g);
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..options.max_iterations {
+        let dangling = rank.iter()
+            .zip(&shares)
+            .filter(|(_, s)| s.is_none())
+            .map(|(r, _)| r)
+            .sum::<f64>();
+        let base = (1.0 - d) / n as f64 + d * dangling / n as f64;
+        let next = step(&rank, &shares, base);
+        let change = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum::<f64>();
+        rank = next;
+        if change < options.tolerance {
+            break;
+        }
+    }
+    rank
+}
+
+/// Brandes' betweenness centrality: for each node, the number of
+/// shortest paths between other pairs of nodes which pass through
+/// it, split evenly between paths of equal length. Edges count as
+/// length 1. If `normalized`, scores are divided by the number of
+/// pairs of other nodes: `(n - 1)(n - 2)` ordered pairs if directed,
+/// half that if not.
+pub fn betweenness_centrality<N, E, Ix>(g: &Graph<N, E, Ix>, normalized: bool) -> Vec<f64>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let n = g.node_count();
+    let mut centrality = vec![0.0; n];
+    let mut order = Vec::with_capacity(n);
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut paths = vec![0.0f64; n];
+    let mut dist = vec![usize::MAX; n];
+    let mut dependency = vec![0.0; n];
+    let mut queue = VecDeque::new();
+    for s in 0..n {
+        order.clear();
+        preds.iter_mut().for_each(Vec::clear);
+        paths.iter_mut().for_each(|p| *p = 0.0);
+        dist.iter_mut().for_each(|d| *d = usize::MAX);
+        paths[s] = 1.0;
+        dist[s] = 0;
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for w in g.neighbors(NodeIx::new(v)) {
+                let w = w.index();
+                if dist[w] == usize::MAX {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    paths[w] += paths[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+        dependency.iter_mut().for_each(|d| *d = 0.0);
+        // settle nodes furthest first, so each node's dependency is
+        // complete before it is passed back to its predecessors
+        for &w in order.iter().rev() {
+            for &v in &preds[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != s {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+    let scale = if normalized && n > 2 {
+        1.0 / ((n - 1) * (n - 2)) as f64
+    } else if !g.is_directed() {
+        // each unordered pair was counted from both ends
+        0.5
+    } else {
+        1.0
+    };
+    centrality.iter_mut().for_each(|c| *c *= scale);
+    centrality
+}
+
+/// Closeness centrality: how near each node is to the nodes it can
+/// reach along out edges, as the reciprocal of their mean distance.
+/// So that nodes reaching little of the graph don't score highly,
+/// this is scaled by the fraction of other nodes reached.
+pub fn closeness_centrality<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<f64>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let n = g.node_count();
+    let mut dist = vec![usize::MAX; n];
+    let mut queue = VecDeque::new();
+    (0..n)
+        .map(|s| {
+            dist.iter_mut().for_each(|d| *d = usize::MAX);
+            dist[s] = 0;
+            queue.push_back(s);
+            let (mut reached, mut total) = (0usize, 0usize);
+            while let Some(v) = queue.pop_front() {
+                reached += 1;
+                total += dist[v];
+                for w in g.neighbors(NodeIx::new(v)) {
+                    if dist[w.index()] == usize::MAX {
+                        dist[w.index()] = dist[v] + 1;
+                        queue.push_back(w.index());
+                    }
+                }
+            }
+            if total == 0 {
+                return 0.0;
+            }
+            let others = (reached - 1) as f64;
+            others / total as f64 * others / (n - 1) as f64
+        })
+        .collect()
+}
+
+/// Degree centrality: the number of neighbors of each node, in
+/// either direction, as a fraction of the other nodes
+pub fn degree_centrality<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<f64>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let n = g.node_count();
+    if n <= 1 {
+        return vec![0.0; n];
+    }
+    g.node_indices()
+        .map(|v| g.neighbors_undirected(v).count() as f64 / (n - 1) as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{algo::test_graph, data::generators};
+
+    type G = Graph<(), ()>;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn page_rank_ranks() {
+        let cycle: G = generators::cycle(4, true);
+        let ranks = page_rank(&cycle, PageRankOptions::default());
+        assert!(ranks.iter().all(|&r| close(r, 0.25)));
+
+        // node 2 is dangling; everything flows towards it
+        let g = test_graph(3, true, &[(0, 1), (1, 2), (0, 2)]);
+        let ranks = page_rank(&g, PageRankOptions { tolerance: 1e-10, ..Default::default() });
+        assert!(close(ranks.iter().sum::<f64>(), 1.0));
+        assert!(ranks[2] > ranks[1] && ranks[1] > ranks[0]);
+        assert!(page_rank(&G::new_directed(), PageRankOptions::default()).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_page_rank_agrees() {
+        let g = test_graph(5, true, &[(0, 1), (1, 2), (2, 0), (3, 2), (4, 3), (0, 4)]);
+        let options = PageRankOptions::default();
+        let serial = page_rank(&g, options);
+        let parallel = parallel_page_rank(&g, options);
+        assert!(serial.iter().zip(&parallel).all(|(&a, &b)| close(a, b)));
+    }
+
+    #[test]
+    fn betweenness_of_star_and_path() {
+        let star: G = generators::star(5, false);
+        let b = betweenness_centrality(&star, false);
+        assert!(close(b[0], 6.0) && close(b[1], 0.0));
+        assert!(close(betweenness_centrality(&star, true)[0], 1.0));
+
+        let path: G = generators::path(3, true);
+        assert_eq!(betweenness_centrality(&path, false), vec![0.0, 1.0, 0.0]);
+        // two equal shortest paths from 0 to 3 share the credit
+        let diamond = test_graph(4, true, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let b = betweenness_centrality(&diamond, false);
+        assert!(close(b[1], 0.5) && close(b[2], 0.5));
+    }
+
+    #[test]
+    fn closeness_and_degree() {
+        let path: G = generators::path(4, false);
+        let c = closeness_centrality(&path);
+        assert!(close(c[0], 3.0 / 6.0) && close(c[1], 3.0 / 4.0));
+        let directed = test_graph(3, true, &[(0, 1)]);
+        let c = closeness_centrality(&directed);
+        assert!(close(c[0], 0.5) && close(c[1], 0.0));
+        assert_eq!(degree_centrality(&path), vec![1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 1.0 / 3.0]);
+    }
+}
@@ -0,0 +1,362 @@
+//! Maximum flow and minimum cut on directed graphs whose edge
+//! weights give capacities. Both algorithms push flow along paths in
+//! the residual graph, where an edge with spare capacity can be
+//! followed forwards and one carrying flow can be followed backwards
+//! to cancel it. All fail with `RecolError::Undirected` if given
+//! an undirected graph; add an edge in each direction to model one.
+//! A missing source or sink fails with `RecolError::NodeNotFound`.
+//!
+use std::{collections::VecDeque, ops::Sub};
+use crate::data::graph::{DefaultIx, EdgeIx, Graph, IndexType, NodeIx};
+use crate::error::{RecolError, RecolResult};
+use super::Measure;
+
+/// A maximum flow: its total value and the flow along each edge,
+/// indexed by edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flow<K> {
+    pub value: K,
+    pub flows: Vec<K>,
+}
+
+/// A minimum cut, found from a maximum flow
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCut<K, Ix = DefaultIx> {
+    /// The total capacity of the cut, equal to the maximum flow
+    pub value: K,
+    /// The nodes still reachable from the source in the residual
+    /// graph, source included, in index order
+    pub source_side: Vec<NodeIx<Ix>>,
+    /// The edges leaving the source side, each saturated
+    pub edges: Vec<EdgeIx<Ix>>,
+}
+
+/// An edge of the residual graph: an edge of the graph, taken
+/// forwards or backwards
+#[derive(Debug, Clone, Copy)]
+struct Arc {
+    edge: usize,
+    forward: bool,
+    from: usize,
+    to: usize,
+}
+
+/// The residual graph of a flow on `g`
+struct Residual<K> {
+    capacity: Vec<K>,
+    flows: Vec<K>,
+    arcs: Vec<Vec<Arc>>,
+}
+
+impl<K> Residual<K>
+where
+    K: Measure + Sub<Output = K>,
+{
+    fn new<N, E, Ix, F>(
+        g: &Graph<N, E, Ix>,
+        source: NodeIx<Ix>,
+        sink: NodeIx<Ix>,
+        mut capacity: F) -> RecolResult<Self>
+    where
+        N: Clone,
+        E: Clone,
+        Ix: IndexType,
+        F: FnMut(&E) -> K,
+    {
+        if !g.is_directed() {
+            return Err(RecolError::Undirected);
+        }
+        for n in [source, sink] {
+            if n.index() >= g.node_count() {
+                return Err(RecolError::node_not_found(n));
+            }
+        }
+        let mut caps = vec![K::default(); g.edge_count()];
+        let mut arcs = vec![Vec::new(); g.node_count()];
+        for e in g.edge_references() {
+            let (a, b, i) = (e.source().index(), e.target().index(), e.id().index());
+            caps[i] = capacity(e.weight());
+            arcs[a].push(Arc { edge: i, forward: true, from: a, to: b });
+            arcs[b].push(Arc { edge: i, forward: false, from: b, to: a });
+        }
+        Ok(Self { capacity: caps, flows: vec![K::default(); g.edge_count()], arcs })
+    }
+
+    fn spare(&self, arc: Arc) -> K {
+        if arc.forward {
+            self.capacity[arc.edge] - self.flows[arc.edge]
+        } else {
+            self.flows[arc.edge]
+        }
+    }
+
+    fn push(&mut self, arc: Arc, amount: K) {
+        let flow = self.flows[arc.edge];
+        self.flows[arc.edge] = if arc.forward { flow + amount } else { flow - amount };
+    }
+
+    /// Push the most flow the path allows, returning how much
+    fn augment(&mut self, path: &[Arc]) -> K {
+        let mut amount = self.spare(path[0]);
+        for &arc in &path[1..] {
+            let spare = self.spare(arc);
+            if spare < amount {
+                amount = spare;
+            }
+        }
+        for &arc in path {
+            self.push(arc, amount);
+        }
+        amount
+    }
+
+    /// The breadth first distance of every node from `source` over
+    /// arcs with spare capacity
+    fn levels(&self, source: usize) -> Vec<Option<usize>> {
+        let zero = K::default();
+        let mut level = vec![None; self.arcs.len()];
+        let mut queue = VecDeque::new();
+        level[source] = Some(0);
+        queue.push_back(source);
+        while let Some(u) = queue.pop_front() {
+            let next = level[u].map(|l| l + 1);
+            for &arc in &self.arcs[u] {
+                if level[arc.to].is_none() && self.spare(arc) > zero {
+                    level[arc.to] = next;
+                    queue.push_back(arc.to);
+                }
+            }
+        }
+        level
+    }
+
+    /// Saturate the residual graph with blocking flows, one phase
+    /// per distinct shortest path length
+    fn dinic(&mut self, s: usize, t: usize) {
+        if s == t {
+            return;
+        }
+        let zero = K::default();
+        let mut path: Vec<Arc> = Vec::new();
+        loop {
+            let mut level = self.levels(s);
+            if level[t].is_none() {
+                return;
+            }
+            // the next arc to try out of each node this phase
+            let mut next = vec![0; self.arcs.len()];
+            let mut u = s;
+            path.clear();
+            loop {
+                if u == t {
+                    self.augment(&path);
+                    path.clear();
+                    u = s;
+                    continue;
+                }
+                let advance = self.arcs[u][next[u]..].iter().position(|&arc| {
+                    level[arc.to].is_some()
+                        && level[arc.to] == level[u].map(|l| l + 1)
+                        && self.spare(arc) > zero
+                });
+                match advance {
+                    Some(i) => {
+                        next[u] += i;
+                        let arc = self.arcs[u][next[u]];
+                        path.push(arc);
+                        u = arc.to;
+                    }
+                    None if u == s => break,
+                    None => {
+                        // a dead end: no more flow passes through `u`
+                        level[u] = None;
+                        u = path.pop().unwrap().from;
+                        next[u] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn into_flow(self, source: usize) -> Flow<K> {
+        let mut value = K::default();
+        for &arc in &self.arcs[source] {
+            value = if arc.forward {
+                value + self.flows[arc.edge]
+            } else {
+                value - self.flows[arc.edge]
+            };
+        }
+        Flow { value, flows: self.flows }
+    }
+}
+
+/// Edmonds-Karp: repeatedly augment along a shortest residual path.
+/// Takes `O(V E²)` time.
+pub fn edmonds_karp<N, E, Ix, F, K>(
+    g: &Graph<N, E, Ix>,
+    source: NodeIx<Ix>,
+    sink: NodeIx<Ix>,
+    capacity: F) -> RecolResult<Flow<K>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure + Sub<Output = K>,
+{
+    let mut res = Residual::new(g, source, sink, capacity)?;
+    let (s, t) = (source.index(), sink.index());
+    let zero = K::default();
+    let mut path = Vec::new();
+    if s == t {
+        return Ok(res.into_flow(s));
+    }
+    loop {
+        let mut pred: Vec<Option<Arc>> = vec![None; g.node_count()];
+        let mut queue = VecDeque::from(vec![s]);
+        while let Some(u) = queue.pop_front() {
+            if u == t {
+                break;
+            }
+            for &arc in &res.arcs[u] {
+                if arc.to != s && pred[arc.to].is_none() && res.spare(arc) > zero {
+                    pred[arc.to] = Some(arc);
+                    queue.push_back(arc.to);
+                }
+            }
+        }
+        if pred[t].is_none() {
+            break;
+        }
+        path.clear();
+        let mut v = t;
+        while let Some(arc) = pred[v] {
+            path.push(arc);
+            v = arc.from;
+        }
+        path.reverse();
+        res.augment(&path);
+    }
+    Ok(res.into_flow(s))
+}
+
+/// Dinic's algorithm: build a level graph of shortest residual
+/// paths, saturate it with a blocking flow, and repeat. Takes
+/// `O(V² E)` time, and much less on most graphs.
+pub fn dinic<N, E, Ix, F, K>(
+    g: &Graph<N, E, Ix>,
+    source: NodeIx<Ix>,
+    sink: NodeIx<Ix>,
+    capacity: F) -> RecolResult<Flow<K>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure + Sub<Output = K>,
+{
+    let mut res = Residual::new(g, source, sink, capacity)?;
+    res.dinic(source.index(), sink.index());
+    Ok(res.into_flow(source.index()))
+}
+
+/// A minimum `source`-`sink` cut: the cheapest set of edges whose
+/// removal leaves no path from `source` to `sink`. Found from the
+/// maximum flow given by `dinic`.
+pub fn min_cut<N, E, Ix, F, K>(
+    g: &Graph<N, E, Ix>,
+    source: NodeIx<Ix>,
+    sink: NodeIx<Ix>,
+    capacity: F) -> RecolResult<MinCut<K, Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure + Sub<Output = K>,
+{
+    let mut res = Residual::new(g, source, sink, capacity)?;
+    res.dinic(source.index(), sink.index());
+    let reachable = res.levels(source.index());
+    let on_source_side = |n: NodeIx<Ix>| reachable[n.index()].is_some();
+    let source_side = g.node_indices().filter(|&n| on_source_side(n)).collect();
+    let edges = g.edge_references()
+        .filter(|e| on_source_side(e.source()) && !on_source_side(e.target()))
+        .map(|e| e.id())
+        .collect();
+    Ok(MinCut { value: res.into_flow(source.index()).value, source_side, edges })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::data::graph::Direction;
+
+    /// The network from CLRS figure 26.1, with a maximum flow of 23
+    fn network() -> (Graph<(), u32>, Vec<NodeIx>) {
+        let mut g = Graph::new_directed();
+        let n = (0..6).map(|_| g.add(())).collect::<Vec<_>>();
+        for &(a, b, c) in &[
+            (0, 1, 16), (0, 2, 13), (2, 1, 4), (1, 3, 12), (3, 2, 9),
+            (2, 4, 14), (4, 3, 7), (3, 5, 20), (4, 5, 4),
+        ] {
+            g.add_edge(n[a], n[b], c);
+        }
+        (g, n)
+    }
+
+    fn check_flow(g: &Graph<(), u32>, n: &[NodeIx], flow: &Flow<u32>) {
+        assert_eq!(flow.value, 23);
+        for e in g.edge_references() {
+            assert!(flow.flows[e.id().index()] <= *e.weight());
+        }
+        for &v in &n[1..5] {
+            let into = g.edges_directed(v, Direction::Incoming).map(|e| flow.flows[e.id().index()]).sum::<u32>();
+            let out = g.edges_directed(v, Direction::Outgoing).map(|e| flow.flows[e.id().index()]).sum::<u32>();
+            assert_eq!(into, out);
+        }
+    }
+
+    #[test]
+    fn max_flow_agrees() {
+        let (g, n) = network();
+        check_flow(&g, &n, &edmonds_karp(&g, n[0], n[5], |&c| c).unwrap());
+        check_flow(&g, &n, &dinic(&g, n[0], n[5], |&c| c).unwrap());
+        assert_eq!(dinic(&g, n[5], n[0], |&c| c).unwrap().value, 0);
+        assert_eq!(edmonds_karp(&g, n[0], n[0], |&c| c).unwrap().value, 0);
+        let halved = dinic(&g, n[0], n[5], |&c| c as f64 / 2.0).unwrap();
+        assert!((halved.value - 11.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn undirected_is_refused() {
+        let mut g = Graph::<(), u32>::new_undirected();
+        let (a, b) = (g.add(()), g.add(()));
+        g.add_edge(a, b, 1);
+        assert!(matches!(edmonds_karp(&g, a, b, |&c| c), Err(RecolError::Undirected)));
+        assert!(matches!(dinic(&g, a, b, |&c| c), Err(RecolError::Undirected)));
+        assert!(matches!(min_cut(&g, a, b, |&c| c), Err(RecolError::Undirected)));
+    }
+
+    #[test]
+    fn missing_ends_are_refused() {
+        let (g, n) = network();
+        let missing = NodeIx::new(6);
+        let refused = |r: RecolResult<u32>| matches!(r, Err(RecolError::NodeNotFound(x)) if x == missing);
+        assert!(refused(edmonds_karp(&g, missing, n[5], |&c| c).map(|f| f.value)));
+        assert!(refused(dinic(&g, n[0], missing, |&c| c).map(|f| f.value)));
+        assert!(refused(min_cut(&g, n[0], missing, |&c| c).map(|c| c.value)));
+    }
+
+    #[test]
+    fn min_cut_separates() {
+        let (g, n) = network();
+        let cut = min_cut(&g, n[0], n[5], |&c| c).unwrap();
+        assert_eq!(cut.value, 23);
+        assert_eq!(cut.source_side, vec![n[0], n[1], n[2], n[4]]);
+        let total = cut.edges.iter().map(|&e| *g.edge_weight(e).unwrap()).sum::<u32>();
+        assert_eq!(total, 23);
+    }
+}
@@ -0,0 +1,404 @@
+//! Bipartiteness and maximum matchings. Edges are taken as
+//! undirected throughout, whatever the graph.
+//!
+use std::collections::VecDeque;
+use crate::data::graph::{DefaultIx, Graph, IndexType, NodeIx};
+
+const NONE: usize = usize::MAX;
+
+/// The two sides of a bipartite component
+pub type Bipartition<Ix = DefaultIx> = (Vec<NodeIx<Ix>>, Vec<NodeIx<Ix>>);
+
+/// Split the component containing `start` into two sides with every
+/// edge running between them, `start`'s side first. Returns `None`
+/// if there is no such split, that is if the component has an odd
+/// cycle or a self loop.
+pub fn is_bipartite_undirected<N, E, Ix>(
+    g: &Graph<N, E, Ix>,
+    start: NodeIx<Ix>) -> Option<Bipartition<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    two_color(g, start, &mut vec![None; g.node_count()])
+}
+
+/// Color the component of `start` in `color`, returning its sides
+fn two_color<N, E, Ix>(
+    g: &Graph<N, E, Ix>,
+    start: NodeIx<Ix>,
+    color: &mut [Option<bool>]) -> Option<Bipartition<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let mut sides = (Vec::new(), Vec::new());
+    let mut queue = VecDeque::new();
+    color[start.index()] = Some(true);
+    queue.push_back(start);
+    while let Some(n) = queue.pop_front() {
+        let side = color[n.index()] == Some(true);
+        if side { sides.0.push(n) } else { sides.1.push(n) }
+        for m in g.neighbors_undirected(n) {
+            match color[m.index()] {
+                None => {
+                    color[m.index()] = Some(!side);
+                    queue.push_back(m);
+                }
+                Some(c) if c == side => return None,
+                Some(_) => {}
+            }
+        }
+    }
+    sides.0.sort();
+    sides.1.sort();
+    Some(sides)
+}
+
+/// A set of edges no two of which share a node, given as each node's
+/// partner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matching<Ix = DefaultIx> {
+    mate: Vec<Option<NodeIx<Ix>>>,
+    len: usize,
+}
+
+impl<Ix: IndexType> Matching<Ix> {
+
+    fn from_mates(mate: &[usize]) -> Self {
+        let mate = mate.iter()
+            .map(|&m| (m != NONE).then(|| NodeIx::new(m)))
+            .collect::<Vec<_>>();
+        let len = mate.iter().filter(|m| m.is_some()).count() / 2;
+        Self { mate, len }
+    }
+
+    /// The node matched with `n`, if any
+    pub fn mate(&self, n: NodeIx<Ix>) -> Option<NodeIx<Ix>> {
+        self.mate.get(n.index()).copied().flatten()
+    }
+
+    pub fn contains_node(&self, n: NodeIx<Ix>) -> bool {
+        self.mate(n).is_some()
+    }
+
+    /// The number of matched pairs
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Whether every node is matched
+    pub fn is_perfect(&self) -> bool {
+        self.len * 2 == self.mate.len()
+    }
+
+    /// The matched pairs, smaller index first, in order
+    pub fn pairs(&self) -> impl Iterator<Item = (NodeIx<Ix>, NodeIx<Ix>)> + '_ {
+        self.mate.iter()
+            .enumerate()
+            .filter_map(|(i, m)| m.filter(|m| i < m.index()).map(|m| (NodeIx::new(i), m)))
+    }
+}
+
+/// The neighbors of each node, loops dropped
+fn adjacency<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<Vec<usize>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    g.node_indices()
+        .map(|n| g.neighbors_undirected(n).filter(|&m| m != n).map(|m| m.index()).collect())
+        .collect()
+}
+
+/// A maximum matching of a bipartite graph by Hopcroft-Karp, in
+/// `O(E √V)` time. Returns `None` if the graph is not bipartite.
+pub fn hopcroft_karp<N, E, Ix>(g: &Graph<N, E, Ix>) -> Option<Matching<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let n = g.node_count();
+    let mut color = vec![None; n];
+    for v in g.node_indices() {
+        if color[v.index()].is_none() {
+            two_color(g, v, &mut color)?;
+        }
+    }
+    let adj = adjacency(g);
+    let left = (0..n).filter(|&v| color[v] == Some(true)).collect::<Vec<_>>();
+    let mut mate = vec![NONE; n];
+    let mut dist = vec![usize::MAX; n];
+    let mut queue = VecDeque::new();
+    loop {
+        // layer the left nodes by alternating path length from a
+        // free left node, up to the layer where the first free right
+        // node is reached: only the shortest augmenting paths are
+        // taken in a phase
+        queue.clear();
+        for &u in &left {
+            dist[u] = if mate[u] == NONE { queue.push_back(u); 0 } else { usize::MAX };
+        }
+        let mut limit = usize::MAX;
+        while let Some(u) = queue.pop_front() {
+            if dist[u] >= limit {
+                break;
+            }
+            for &v in &adj[u] {
+                match mate[v] {
+                    NONE => limit = limit.min(dist[u] + 1),
+                    w if dist[w] == usize::MAX => {
+                        dist[w] = dist[u] + 1;
+                        queue.push_back(w);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if limit == usize::MAX {
+            break;
+        }
+        // augment along vertex disjoint shortest paths
+        let mut next = vec![0; n];
+        let mut stack = Vec::new();
+        for &root in &left {
+            if mate[root] != NONE {
+                continue;
+            }
+            stack.clear();
+            stack.push(root);
+            while let Some(&u) = stack.last() {
+                let v = match adj[u].get(next[u]) {
+                    Some(&v) => v,
+                    None => {
+                        dist[u] = usize::MAX;
+                        stack.pop();
+                        if let Some(&p) = stack.last() {
+                            next[p] += 1;
+                        }
+                        continue;
+                    }
+                };
+                match mate[v] {
+                    NONE if dist[u] + 1 == limit => {
+                        for &x in &stack {
+                            let y = adj[x][next[x]];
+                            mate[x] = y;
+                            mate[y] = x;
+                        }
+                        break;
+                    }
+                    w if w != NONE && dist[w] == dist[u] + 1 => stack.push(w),
+                    _ => next[u] += 1,
+                }
+            }
+        }
+    }
+    Some(Matching::from_mates(&mate))
+}
+
+/// A maximum matching of any graph by Edmonds' blossom algorithm, in
+/// `O(V³)` time. Odd cycles found while searching for an augmenting
+/// path are contracted into their base node, with parent links set
+/// so the path can still be traced through them.
+pub fn maximum_matching<N, E, Ix>(g: &Graph<N, E, Ix>) -> Matching<Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let mut blossom = Blossom::new(adjacency(g));
+    for root in 0..blossom.adj.len() {
+        if blossom.mate[root] == NONE {
+            if let Some(end) = blossom.find_path(root) {
+                blossom.augment(end);
+            }
+        }
+    }
+    Matching::from_mates(&blossom.mate)
+}
+
+/// The state of a search for an augmenting path from one root
+struct Blossom {
+    adj: Vec<Vec<usize>>,
+    mate: Vec<usize>,
+    /// The node each node leads back towards the root through, for
+    /// nodes at odd depth
+    parent: Vec<usize>,
+    /// The base of the blossom each node has been contracted into
+    base: Vec<usize>,
+    used: Vec<bool>,
+    in_blossom: Vec<bool>,
+    queue: VecDeque<usize>,
+}
+
+impl Blossom {
+    fn new(adj: Vec<Vec<usize>>) -> Self {
+        let n = adj.len();
+        Self {
+            adj,
+            mate: vec![NONE; n],
+            parent: vec![NONE; n],
+            base: (0..n).collect(),
+            used: vec![false; n],
+            in_blossom: vec![false; n],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// The nearest common ancestor of the blossoms of `a` and `b` in
+    /// the alternating tree
+    fn common_base(&self, mut a: usize, mut b: usize) -> usize {
+        let mut seen = vec![false; self.adj.len()];
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            if self.mate[a] == NONE {
+                break;
+            }
+            a = self.parent[self.mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.parent[self.mate[b]];
+        }
+    }
+
+    /// Mark the blossoms on the tree path from `v` up to `base`,
+    /// pointing their odd nodes back towards `child`
+    fn mark_path(&mut self, mut v: usize, base: usize, mut child: usize) {
+        while self.base[v] != base {
+            self.in_blossom[self.base[v]] = true;
+            self.in_blossom[self.base[self.mate[v]]] = true;
+            self.parent[v] = child;
+            child = self.mate[v];
+            v = self.parent[self.mate[v]];
+        }
+    }
+
+    /// The free node ending an augmenting path from `root`, if any
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        let n = self.adj.len();
+        self.used.iter_mut().for_each(|u| *u = false);
+        self.parent.iter_mut().for_each(|p| *p = NONE);
+        for (i, b) in self.base.iter_mut().enumerate() {
+            *b = i;
+        }
+        self.used[root] = true;
+        self.queue.clear();
+        self.queue.push_back(root);
+        while let Some(v) = self.queue.pop_front() {
+            for i in 0..self.adj[v].len() {
+                let to = self.adj[v][i];
+                if self.base[v] == self.base[to] || self.mate[v] == to {
+                    continue;
+                }
+                if to == root || self.mate[to] != NONE && self.parent[self.mate[to]] != NONE {
+                    // an odd cycle: contract it into its base
+                    let base = self.common_base(v, to);
+                    self.in_blossom.iter_mut().for_each(|b| *b = false);
+                    self.mark_path(v, base, to);
+                    self.mark_path(to, base, v);
+                    for j in 0..n {
+                        if self.in_blossom[self.base[j]] {
+                            self.base[j] = base;
+                            if !self.used[j] {
+                                self.used[j] = true;
+                                self.queue.push_back(j);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NONE {
+                    self.parent[to] = v;
+                    if self.mate[to] == NONE {
+                        return Some(to);
+                    }
+                    let next = self.mate[to];
+                    self.used[next] = true;
+                    self.queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Flip the matching along the path ending at `v`
+    fn augment(&mut self, mut v: usize) {
+        while v != NONE {
+            let pv = self.parent[v];
+            let next = self.mate[pv];
+            self.mate[v] = pv;
+            self.mate[pv] = v;
+            v = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use crate::{algo::test_graph, data::generators};
+
+    fn is_matching(g: &Graph<(), ()>, m: &Matching) -> bool {
+        m.pairs().all(|(a, b)| g.contains_edge(a, b) || g.contains_edge(b, a))
+            && g.node_indices().all(|n| m.mate(n).is_none_or(|p| m.mate(p) == Some(n)))
+    }
+
+    #[test]
+    fn bipartite_check() {
+        let square = test_graph(5, false, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        let n = |i| NodeIx::new(i);
+        assert_eq!(is_bipartite_undirected(&square, n(1)), Some((vec![n(1), n(3)], vec![n(0), n(2)])));
+        assert_eq!(is_bipartite_undirected(&square, n(4)), Some((vec![n(4)], vec![])));
+        let triangle = test_graph(3, false, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(is_bipartite_undirected(&triangle, n(0)), None);
+        assert!(hopcroft_karp(&triangle).is_none());
+    }
+
+    #[test]
+    fn blossom_needs_contraction() {
+        // a triangle with a tail at each corner: greedy choices inside
+        // the triangle must be undone through the blossom
+        let g = test_graph(6, false, &[(0, 1), (1, 2), (2, 0), (0, 3), (1, 4), (2, 5)]);
+        let m = maximum_matching(&g);
+        assert!(m.is_perfect() && is_matching(&g, &m));
+        // the Petersen graph has a perfect matching
+        let petersen = test_graph(10, false, &[
+            (0, 1), (1, 2), (2, 3), (3, 4), (4, 0),
+            (0, 5), (1, 6), (2, 7), (3, 8), (4, 9),
+            (5, 7), (7, 9), (9, 6), (6, 8), (8, 5),
+        ]);
+        let m = maximum_matching(&petersen);
+        assert_eq!(m.len(), 5);
+        assert!(is_matching(&petersen, &m));
+    }
+
+    #[test]
+    fn matchings_agree_on_bipartite_graphs() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let (l, r) = (rng.gen_range(1..12), rng.gen_range(1..12));
+            let edges = (0..rng.gen_range(0..30))
+                .map(|_| (rng.gen_range(0..l), l + rng.gen_range(0..r)))
+                .collect::<Vec<_>>();
+            let g = test_graph(l + r, false, &edges);
+            let hk = hopcroft_karp(&g).unwrap();
+            let blossom = maximum_matching(&g);
+            assert!(is_matching(&g, &hk) && is_matching(&g, &blossom));
+            assert_eq!(hk.len(), blossom.len());
+        }
+        // a long path takes several phases, with longer augmenting
+        // paths in each
+        let path: Graph<(), ()> = generators::path(10, false);
+        assert!(hopcroft_karp(&path).unwrap().is_perfect());
+    }
+}
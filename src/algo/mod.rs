@@ -0,0 +1,85 @@
+//! Graph algorithms built on top of `data::graph`.
+//!
+use std::{cmp::Ordering, fmt, ops::Add};
+
+pub mod centrality;
+pub mod flow;
+pub mod isomorphism;
+pub mod matching;
+pub mod mst;
+pub mod operators;
+pub mod scc;
+pub mod shortest_path;
+pub mod toposort;
+
+pub use centrality::{
+    betweenness_centrality, closeness_centrality, degree_centrality, page_rank, PageRankOptions,
+};
+#[cfg(feature = "rayon")]
+pub use centrality::parallel_page_rank;
+pub use flow::{dinic, edmonds_karp, min_cut, Flow, MinCut};
+pub use isomorphism::{
+    is_isomorphic, is_isomorphic_matching, is_isomorphic_subgraph, subgraph_isomorphisms_iter,
+    Isomorphisms,
+};
+pub use matching::{hopcroft_karp, is_bipartite_undirected, maximum_matching, Bipartition, Matching};
+pub use mst::{connected_components, kruskal, min_spanning_tree, prim};
+pub use operators::{complement, difference, intersection, symmetric_difference, union};
+pub use scc::{condensation, kosaraju_scc, tarjan_scc};
+pub use shortest_path::{astar, bellman_ford, dijkstra, NegativeCycle, Paths};
+pub use toposort::{is_cyclic_directed, toposort, Acyclic, Cycle};
+
+/// A graph of `n` unweighted nodes joined by `edges`, for tests
+#[cfg(test)]
+pub(crate) fn test_graph(n: usize, directed: bool, edges: &[(usize, usize)]) -> crate::data::Graph<(), ()> {
+    use crate::data::graph::{Graph, NodeIx};
+    let mut g = if directed { Graph::new_directed() } else { Graph::new_undirected() };
+    for _ in 0..n {
+        g.add(());
+    }
+    g.extend_with_edges(edges.iter().map(|&(a, b)| (NodeIx::new(a), NodeIx::new(b), ())));
+    g
+}
+
+/// A path cost. Implemented for every type which can be added and
+/// compared, so both integer and float weights work. The `Default`
+/// value is taken as zero.
+pub trait Measure: fmt::Debug + PartialOrd + Add<Output = Self> + Default + Copy {}
+
+impl<M> Measure for M
+where
+    M: fmt::Debug + PartialOrd + Add<Output = M> + Default + Copy,
+{}
+
+/// A heap entry which orders by smallest score first, for use in a
+/// max-heap `BinaryHeap`. Incomparable scores (NaN) sort last.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MinScored<K, T>(pub K, pub T);
+
+impl<K: PartialOrd, T> PartialEq for MinScored<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: PartialOrd, T> Eq for MinScored<K, T> {}
+
+impl<K: PartialOrd, T> PartialOrd for MinScored<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: PartialOrd, T> Ord for MinScored<K, T> {
+    #[allow(clippy::eq_op)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (&self.0, &other.0);
+        match a.partial_cmp(b) {
+            Some(ord) => ord.reverse(),
+            // a NaN score is never equal to itself
+            None if a != a && b != b => Ordering::Equal,
+            None if a != a => Ordering::Less,
+            None => Ordering::Greater,
+        }
+    }
+}
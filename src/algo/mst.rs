@@ -0,0 +1,161 @@
+//! Minimum spanning trees and connected components, both built on
+//! `UnionFind`. Edge directions are ignored throughout, so on a
+//! directed graph these work on the underlying undirected graph.
+//!
+//! A disconnected graph has no spanning tree; the algorithms then
+//! return a minimum spanning forest, one tree per component.
+//!
+use std::{cmp::Ordering, collections::BinaryHeap};
+use crate::data::{graph::{EdgeIx, Graph, IndexType, NodeIx}, union_find::UnionFind};
+use super::{Measure, MinScored};
+
+/// Kruskal's algorithm. Returns the edges of a minimum spanning
+/// forest in order of increasing cost.
+pub fn kruskal<N, E, Ix, F, K>(g: &Graph<N, E, Ix>, mut edge_cost: F) -> Vec<EdgeIx<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure,
+{
+    let mut sorted = g.edge_indices()
+        .map(|e| (edge_cost(&g.edges()[e.index()].weight), e))
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    let mut sets = UnionFind::<Ix>::new(g.node_count());
+    let mut tree = Vec::with_capacity(g.node_count().saturating_sub(1));
+    for (_, e) in sorted {
+        let (a, b) = g.edge_endpoints(e).unwrap();
+        if sets.union(Ix::new(a.index()), Ix::new(b.index())) {
+            tree.push(e);
+        }
+    }
+    tree
+}
+
+/// Prim's algorithm. Returns the edges of a minimum spanning forest,
+/// each tree grown outward from its lowest indexed node.
+pub fn prim<N, E, Ix, F, K>(g: &Graph<N, E, Ix>, mut edge_cost: F) -> Vec<EdgeIx<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure,
+{
+    let mut in_tree = vec![false; g.node_count()];
+    let mut tree = Vec::with_capacity(g.node_count().saturating_sub(1));
+    let mut heap = BinaryHeap::new();
+    for root in g.node_indices() {
+        if in_tree[root.index()] {
+            continue;
+        }
+        // The root enters the tree without an edge
+        heap.push(MinScored(K::default(), (None, root)));
+        while let Some(MinScored(_, (e, n))) = heap.pop() {
+            if std::mem::replace(&mut in_tree[n.index()], true) {
+                continue;
+            }
+            tree.extend(e);
+            let mut walk = g.neighbors_undirected(n).detach();
+            while let Some((e, next)) = walk.next(g) {
+                if !in_tree[next.index()] {
+                    heap.push(MinScored(edge_cost(&g.edges()[e.index()].weight), (Some(e), next)));
+                }
+            }
+        }
+    }
+    tree
+}
+
+/// A graph with the nodes of `g`, at the same indices, joined only by
+/// the edges of a minimum spanning forest (found by `kruskal`)
+pub fn min_spanning_tree<N, E, Ix, F, K>(g: &Graph<N, E, Ix>, edge_cost: F) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    F: FnMut(&E) -> K,
+    K: Measure,
+{
+    let mut tree = Graph::new_undirected();
+    for node in g.nodes() {
+        tree.add(node.weight.clone());
+    }
+    for e in kruskal(g, edge_cost) {
+        let (a, b) = g.edge_endpoints(e).unwrap();
+        tree.add_edge(a, b, g.edges()[e.index()].weight.clone());
+    }
+    tree
+}
+
+/// The connected components of `g`, ignoring edge direction (so the
+/// weakly connected components of a directed graph). Components are
+/// ordered by their lowest indexed node, and nodes within each
+/// component by index.
+pub fn connected_components<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<Vec<NodeIx<Ix>>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let mut sets = UnionFind::<Ix>::new(g.node_count());
+    for e in g.edges() {
+        sets.union(Ix::new(e.src().index()), Ix::new(e.dest().index()));
+    }
+    let mut slot = vec![usize::MAX; g.node_count()];
+    let mut components: Vec<Vec<NodeIx<Ix>>> = Vec::new();
+    for (n, root) in sets.into_labeling().into_iter().enumerate() {
+        let slot = &mut slot[root.index()];
+        if *slot == usize::MAX {
+            *slot = components.len();
+            components.push(Vec::new());
+        }
+        components[*slot].push(NodeIx::new(n));
+    }
+    components
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    /// A weighted square with one diagonal, and a separate pair
+    fn graph() -> (Graph<(), u32>, Vec<NodeIx>) {
+        let mut g = Graph::new_undirected();
+        let n = (0..6).map(|_| g.add(())).collect::<Vec<_>>();
+        for &(a, b, w) in &[(0, 1, 4), (1, 2, 1), (2, 3, 3), (3, 0, 2), (0, 2, 5), (4, 5, 7)] {
+            g.add_edge(n[a], n[b], w);
+        }
+        (g, n)
+    }
+
+    fn total(g: &Graph<(), u32>, tree: &[EdgeIx]) -> u32 {
+        tree.iter().map(|&e| *g.edge_weight(e).unwrap()).sum()
+    }
+
+    #[test]
+    fn kruskal_and_prim_agree() {
+        let (g, _) = graph();
+        let k = kruskal(&g, |w| *w);
+        let p = prim(&g, |w| *w);
+        assert_eq!(k.len(), 4);
+        assert_eq!(p.len(), 4);
+        assert_eq!(total(&g, &k), 13);
+        assert_eq!(total(&g, &p), 13);
+        let tree = min_spanning_tree(&g, |w| *w);
+        assert_eq!(tree.node_count(), 6);
+        assert_eq!(tree.edge_count(), 4);
+        assert_eq!(connected_components(&tree).len(), 2);
+    }
+
+    #[test]
+    fn components() {
+        let (mut g, n) = graph();
+        g.add(());
+        let components = connected_components(&g);
+        assert_eq!(components, vec![n[..4].to_vec(), n[4..].to_vec(), vec![NodeIx::new(6)]]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Set operations on graphs. Nodes of the two graphs are matched by
+//! a key computed from their weights, and edges by the keys of their
+//! endpoints (in either order if undirected), so the graphs needn't
+//! share indices. Keys are expected to be unique within each graph;
+//! where they aren't, the first node with a key stands for it.
+//!
+//! The nodes and edges of the first graph come first in each result,
+//! at their original indices where they survive, followed by any
+//! taken from the second.
+//!
+//! The binary operations panic if one graph is directed and the
+//! other isn't, since their edges can't be matched up.
+//!
+use std::{collections::HashMap, hash::Hash};
+use crate::data::graph::{Graph, IndexType, NodeIx};
+
+/// Panic unless `a` and `b` are both directed or both undirected
+fn check_directed<N, E, Ix>(a: &Graph<N, E, Ix>, b: &Graph<N, E, Ix>)
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    assert_eq!(a.is_directed(), b.is_directed(), "combining directed and undirected graphs");
+}
+
+/// The index of the first node with each key
+fn first_with_key<Q: Eq + Hash>(keys: &[Q]) -> HashMap<&Q, usize> {
+    let mut first = HashMap::with_capacity(keys.len());
+    for (i, k) in keys.iter().enumerate() {
+        first.entry(k).or_insert(i);
+    }
+    first
+}
+
+/// The index of the first edge joining each pair of keys
+fn edges_by_key<'q, N, E, Ix, Q>(g: &Graph<N, E, Ix>, keys: &'q [Q]) -> HashMap<(&'q Q, &'q Q), usize>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    Q: Eq + Hash,
+{
+    let mut edges = HashMap::with_capacity(g.edge_count());
+    for e in g.edge_references() {
+        let (s, t) = (&keys[e.source().index()], &keys[e.target().index()]);
+        edges.entry((s, t)).or_insert(e.id().index());
+        if !g.is_directed() {
+            edges.entry((t, s)).or_insert(e.id().index());
+        }
+    }
+    edges
+}
+
+fn keys<N, E, Ix, K, Q>(g: &Graph<N, E, Ix>, key: &mut K) -> Vec<Q>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+{
+    g.nodes().iter().map(|n| key(&n.weight)).collect()
+}
+
+/// The nodes of both graphs, and the edges of each: an edge of `a`
+/// whose endpoints are joined in `b` too is replaced by the result
+/// of `shared` on the two weights, or dropped if that is `None`.
+/// Edges of `b` joining nodes joined in `a` are left out.
+fn join<N, E, Ix, K, Q, FN, FE>(
+    a: &Graph<N, E, Ix>,
+    b: &Graph<N, E, Ix>,
+    mut key: K,
+    mut merge_node: FN,
+    mut shared: FE) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+    Q: Eq + Hash,
+    FN: FnMut(&N, &N) -> N,
+    FE: FnMut(&E, &E) -> Option<E>,
+{
+    check_directed(a, b);
+    let (ka, kb) = (keys(a, &mut key), keys(b, &mut key));
+    let (a_nodes, b_nodes) = (first_with_key(&ka), first_with_key(&kb));
+    let (a_edges, b_edges) = (edges_by_key(a, &ka), edges_by_key(b, &kb));
+    let b_weight = |j: usize| b.weight(NodeIx::new(j)).unwrap();
+
+    let mut g = a.filter_map(
+        |n, w| Some(match b_nodes.get(&ka[n.index()]) {
+            Some(&j) => merge_node(w, b_weight(j)),
+            None => w.clone(),
+        }),
+        |e, w| {
+            let (s, t) = a.edge_endpoints(e).unwrap();
+            match b_edges.get(&(&ka[s.index()], &ka[t.index()])) {
+                Some(&j) => shared(w, &b.edges()[j].weight),
+                None => Some(w.clone()),
+            }
+        });
+    let mut added = HashMap::new();
+    let mut moved = Vec::with_capacity(kb.len());
+    for (j, k) in kb.iter().enumerate() {
+        let n = match a_nodes.get(k) {
+            Some(&i) => NodeIx::new(i),
+            None => *added.entry(k).or_insert_with(|| g.add(b_weight(j).clone())),
+        };
+        moved.push(n);
+    }
+    for e in b.edge_references() {
+        let (s, t) = (e.source().index(), e.target().index());
+        if !a_edges.contains_key(&(&kb[s], &kb[t])) {
+            g.add_edge(moved[s], moved[t], e.weight().clone());
+        }
+    }
+    g
+}
+
+/// Every node and edge of either graph. Nodes and edges found in
+/// both have their weights combined by `merge_node` and `merge_edge`,
+/// called with the weight from `a` first.
+pub fn union<N, E, Ix, K, Q, FN, FE>(
+    a: &Graph<N, E, Ix>,
+    b: &Graph<N, E, Ix>,
+    key: K,
+    merge_node: FN,
+    mut merge_edge: FE) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+    Q: Eq + Hash,
+    FN: FnMut(&N, &N) -> N,
+    FE: FnMut(&E, &E) -> E,
+{
+    join(a, b, key, merge_node, |x, y| Some(merge_edge(x, y)))
+}
+
+/// The nodes and edges of `a` also found in `b`, with their weights
+/// from `a`
+pub fn intersection<N, E, Ix, K, Q>(a: &Graph<N, E, Ix>, b: &Graph<N, E, Ix>, mut key: K) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+    Q: Eq + Hash,
+{
+    check_directed(a, b);
+    let (ka, kb) = (keys(a, &mut key), keys(b, &mut key));
+    let b_nodes = first_with_key(&kb);
+    let b_edges = edges_by_key(b, &kb);
+    a.filter_map(
+        |n, w| b_nodes.contains_key(&ka[n.index()]).then(|| w.clone()),
+        |e, w| {
+            let (s, t) = a.edge_endpoints(e).unwrap();
+            b_edges.contains_key(&(&ka[s.index()], &ka[t.index()])).then(|| w.clone())
+        })
+}
+
+/// Every node of `a`, and the edges of `a` not found in `b`
+pub fn difference<N, E, Ix, K, Q>(a: &Graph<N, E, Ix>, b: &Graph<N, E, Ix>, mut key: K) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+    Q: Eq + Hash,
+{
+    check_directed(a, b);
+    let (ka, kb) = (keys(a, &mut key), keys(b, &mut key));
+    let b_edges = edges_by_key(b, &kb);
+    a.filter_map(
+        |_, w| Some(w.clone()),
+        |e, w| {
+            let (s, t) = a.edge_endpoints(e).unwrap();
+            (!b_edges.contains_key(&(&ka[s.index()], &ka[t.index()]))).then(|| w.clone())
+        })
+}
+
+/// Every node of either graph, with its weight from `a` if found in
+/// both, and the edges found in only one of them
+pub fn symmetric_difference<N, E, Ix, K, Q>(a: &Graph<N, E, Ix>, b: &Graph<N, E, Ix>, key: K) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    K: FnMut(&N) -> Q,
+    Q: Eq + Hash,
+{
+    join(a, b, key, |x, _| x.clone(), |_, _| None)
+}
+
+/// The same nodes as `g`, joined wherever `g` has no edge between
+/// them, with default weights. There are no self loops. In a
+/// directed graph each direction is considered separately.
+pub fn complement<N, E, Ix>(g: &Graph<N, E, Ix>) -> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut out = g.filter_map(|_, w| Some(w.clone()), |_, _| None);
+    let mut joined = vec![false; g.node_count()];
+    for a in g.node_indices() {
+        for b in g.neighbors(a) {
+            joined[b.index()] = true;
+        }
+        // undirected pairs are considered once, from their lower end
+        let from = if g.is_directed() { 0 } else { a.index() + 1 };
+        for (b, &j) in joined.iter().enumerate().skip(from) {
+            if b != a.index() && !j {
+                out.add_edge(a, NodeIx::new(b), E::default());
+            }
+        }
+        for b in g.neighbors(a) {
+            joined[b.index()] = false;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    /// A dependency graph over named packages, with version weights
+    fn deps(directed: bool, edges: &[(&'static str, &'static str, u32)]) -> Graph<&'static str, u32> {
+        let mut g = if directed { Graph::new_directed() } else { Graph::new_undirected() };
+        let mut ix = HashMap::new();
+        for &(a, b, w) in edges {
+            let a = *ix.entry(a).or_insert_with(|| g.add(a));
+            let b = *ix.entry(b).or_insert_with(|| g.add(b));
+            g.add_edge(a, b, w);
+        }
+        g
+    }
+
+    fn edges(g: &Graph<&'static str, u32>) -> Vec<(&'static str, &'static str, u32)> {
+        g.edge_references()
+            .map(|e| (*g.weight(e.source()).unwrap(), *g.weight(e.target()).unwrap(), *e.weight()))
+            .collect()
+    }
+
+    #[test]
+    fn union_merges() {
+        let a = deps(true, &[("app", "log", 1), ("app", "http", 2)]);
+        let b = deps(true, &[("http", "tls", 3), ("app", "http", 4)]);
+        let g = union(&a, &b, |&n| n, |&x, _| x, |&x, &y| x.max(y));
+        let nodes = g.node_indices().map(|n| *g.weight(n).unwrap()).collect::<Vec<_>>();
+        assert_eq!(nodes, vec!["app", "log", "http", "tls"]);
+        assert_eq!(edges(&g), vec![("app", "log", 1), ("app", "http", 4), ("http", "tls", 3)]);
+    }
+
+    #[test]
+    fn intersection_and_differences() {
+        let a = deps(false, &[("x", "y", 1), ("y", "z", 2), ("z", "w", 3)]);
+        let b = deps(false, &[("z", "y", 5), ("w", "v", 6)]);
+        let key = |&n: &&'static str| n;
+
+        let both = intersection(&a, &b, key);
+        assert_eq!(both.node_count(), 3);
+        assert_eq!(edges(&both), vec![("y", "z", 2)]);
+
+        let only_a = difference(&a, &b, key);
+        assert_eq!(only_a.node_count(), 4);
+        assert_eq!(edges(&only_a), vec![("x", "y", 1), ("z", "w", 3)]);
+
+        let either = symmetric_difference(&a, &b, key);
+        assert_eq!(either.node_count(), 5);
+        assert_eq!(edges(&either), vec![("x", "y", 1), ("z", "w", 3), ("w", "v", 6)]);
+
+        // direction matters between directed graphs
+        let a = deps(true, &[("x", "y", 1)]);
+        let b = deps(true, &[("y", "x", 1)]);
+        assert_eq!(intersection(&a, &b, key).edge_count(), 0);
+    }
+
+    #[test]
+    fn mixed_directions_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        let a = deps(true, &[("x", "y", 1)]);
+        let b = deps(false, &[("y", "x", 1)]);
+        let key = |&n: &&'static str| n;
+        assert!(catch_unwind(AssertUnwindSafe(|| intersection(&a, &b, key))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| difference(&a, &b, key))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| symmetric_difference(&b, &a, key))).is_err());
+    }
+
+    #[test]
+    fn complement_of_path() {
+        let path = deps(false, &[("a", "b", 1), ("b", "c", 1), ("c", "d", 1)]);
+        let c = complement(&path);
+        assert_eq!(c.node_count(), 4);
+        assert_eq!(edges(&c), vec![("a", "c", 0), ("a", "d", 0), ("b", "d", 0)]);
+        assert_eq!(complement(&c).edge_count(), 3);
+
+        let directed = deps(true, &[("a", "b", 1)]);
+        assert_eq!(edges(&complement(&directed)), vec![("b", "a", 0)]);
+    }
+}
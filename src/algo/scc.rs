@@ -0,0 +1,212 @@
+//! Strongly connected components, and the condensation of a graph
+//! into one node per component. Both algorithms keep an explicit
+//! stack instead of recursing, so deep graphs can't overflow.
+//!
+use crate::data::graph::{Direction, Graph, IndexType, NodeIx};
+use crate::visit::{Dfs, DfsEvent, DfsEvents, VisitMap};
+
+/// Tarjan's algorithm. Components are returned in reverse
+/// topological order: no component has an edge to a later one.
+pub fn tarjan_scc<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<Vec<NodeIx<Ix>>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    const UNVISITED: usize = usize::MAX;
+    let n = g.node_count();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut calls = Vec::new();
+    let mut next_index = 0;
+    let mut sccs = Vec::new();
+
+    for root in g.node_indices() {
+        if index[root.index()] != UNVISITED {
+            continue;
+        }
+        // The next node to visit, pushed at the top of the loop
+        let mut pending = Some(root);
+        loop {
+            if let Some(v) = pending.take() {
+                index[v.index()] = next_index;
+                lowlink[v.index()] = next_index;
+                next_index += 1;
+                on_stack[v.index()] = true;
+                stack.push(v);
+                calls.push((v, g.neighbors(v).detach()));
+            }
+            let Some((v, walk)) = calls.last_mut() else { break };
+            let v = *v;
+            match walk.next_node(g) {
+                Some(w) if index[w.index()] == UNVISITED => pending = Some(w),
+                Some(w) => {
+                    if on_stack[w.index()] {
+                        lowlink[v.index()] = lowlink[v.index()].min(index[w.index()]);
+                    }
+                }
+                None => {
+                    calls.pop();
+                    if lowlink[v.index()] == index[v.index()] {
+                        let mut scc = Vec::new();
+                        while let Some(w) = stack.pop() {
+                            on_stack[w.index()] = false;
+                            scc.push(w);
+                            if w == v { break; }
+                        }
+                        sccs.push(scc);
+                    }
+                    if let Some((u, _)) = calls.last() {
+                        lowlink[u.index()] = lowlink[u.index()].min(lowlink[v.index()]);
+                    }
+                }
+            }
+        }
+    }
+    sccs
+}
+
+/// Kosaraju's algorithm. Components are returned in reverse
+/// topological order, like `tarjan_scc`, though the two may order
+/// unrelated components differently.
+pub fn kosaraju_scc<N, E, Ix>(g: &Graph<N, E, Ix>) -> Vec<Vec<NodeIx<Ix>>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let mut finish_order = Vec::with_capacity(g.node_count());
+    let mut events: Option<DfsEvents<Ix>> = None;
+    // Finish order over the reversed graph
+    for root in g.node_indices() {
+        let events = match events.as_mut() {
+            Some(ev) if ev.is_discovered(root) => continue,
+            Some(ev) => { ev.move_to(g, root); ev }
+            None => events.get_or_insert_with(|| DfsEvents::with_direction(g, root, Direction::Incoming)),
+        };
+        while let Some(event) = events.next(g) {
+            if let DfsEvent::Finish(n, _) = event {
+                finish_order.push(n);
+            }
+        }
+    }
+    let mut sccs = Vec::new();
+    let mut dfs: Option<Dfs<NodeIx<Ix>>> = None;
+    for &root in finish_order.iter().rev() {
+        let dfs = match dfs.as_mut() {
+            Some(dfs) if dfs.discovered.is_visited(&root) => continue,
+            Some(dfs) => { dfs.move_to(root); dfs }
+            None => dfs.get_or_insert_with(|| Dfs::new(g, root)),
+        };
+        let mut scc = Vec::new();
+        while let Some(n) = dfs.next(g) {
+            scc.push(n);
+        }
+        sccs.push(scc);
+    }
+    sccs
+}
+
+/// Collapse each strongly connected component of `g` into a single
+/// node holding the weights of its members. Edges are carried over
+/// between the components of their endpoints. If `make_acyclic` is
+/// set, edges inside a component are dropped and parallel edges
+/// between two components are merged, keeping the last weight, so
+/// the result is a DAG.
+pub fn condensation<N, E, Ix>(g: &Graph<N, E, Ix>, make_acyclic: bool) -> Graph<Vec<N>, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    let sccs = kosaraju_scc(g);
+    let mut condensed = if g.is_directed() {
+        Graph::new_directed()
+    } else {
+        Graph::new_undirected()
+    };
+    let mut component = vec![NodeIx::end(); g.node_count()];
+    for scc in &sccs {
+        let weights = scc.iter().map(|&n| g.weight(n).unwrap().clone()).collect();
+        let c = condensed.add(weights);
+        for n in scc {
+            component[n.index()] = c;
+        }
+    }
+    for e in g.edge_indices() {
+        let (a, b) = g.edge_endpoints(e).unwrap();
+        let (ca, cb) = (component[a.index()], component[b.index()]);
+        let weight = g.edge_weight(e).unwrap().clone();
+        if !make_acyclic {
+            condensed.add_edge(ca, cb, weight);
+        } else if ca != cb {
+            condensed.update_edge(ca, cb, weight);
+        }
+    }
+    condensed
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    /// Two 3-cycles joined by one edge, plus an isolated node
+    fn graph() -> (Graph<usize, ()>, Vec<NodeIx>) {
+        let mut g = Graph::new_directed();
+        let n = (0..7).map(|i| g.add(i)).collect::<Vec<_>>();
+        for &(a, b) in &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)] {
+            g.add_edge(n[a], n[b], ());
+        }
+        (g, n)
+    }
+
+    fn sorted(mut sccs: Vec<Vec<NodeIx>>) -> Vec<Vec<NodeIx>> {
+        sccs.iter_mut().for_each(|c| c.sort());
+        sccs.sort();
+        sccs
+    }
+
+    #[test]
+    fn tarjan_and_kosaraju_agree() {
+        let (g, n) = graph();
+        let expected = vec![vec![n[0], n[1], n[2]], vec![n[3], n[4], n[5]], vec![n[6]]];
+        let tarjan = tarjan_scc(&g);
+        let kosaraju = kosaraju_scc(&g);
+        // The sink component comes before its predecessor
+        let pos = |sccs: &Vec<Vec<NodeIx>>, x| sccs.iter().position(|c| c.contains(&x));
+        assert!(pos(&tarjan, n[3]) < pos(&tarjan, n[0]));
+        assert!(pos(&kosaraju, n[3]) < pos(&kosaraju, n[0]));
+        assert_eq!(sorted(tarjan), expected);
+        assert_eq!(sorted(kosaraju), expected);
+    }
+
+    #[test]
+    fn deep_path_does_not_overflow() {
+        let mut g = Graph::<(), ()>::new_directed();
+        let mut prev = g.add(());
+        let first = prev;
+        for _ in 0..200_000 {
+            let next = g.add(());
+            g.add_edge(prev, next, ());
+            prev = next;
+        }
+        g.add_edge(prev, first, ());
+        assert_eq!(tarjan_scc(&g).len(), 1);
+        assert_eq!(kosaraju_scc(&g).len(), 1);
+    }
+
+    #[test]
+    fn condensation_is_acyclic() {
+        let (g, _) = graph();
+        let c = condensation(&g, true);
+        assert_eq!(c.node_count(), 3);
+        assert_eq!(c.edge_count(), 1);
+        assert!(!crate::algo::is_cyclic_directed(&c));
+        let c = condensation(&g, false);
+        assert_eq!(c.edge_count(), 7);
+        assert!(c.nodes().iter().any(|n| n.weight.len() == 3));
+    }
+}
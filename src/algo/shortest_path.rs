@@ -0,0 +1,296 @@
+//! Single source shortest paths: Dijkstra, Bellman-Ford and A*.
+//!
+//! Each returns a `Paths` holding the distance and predecessor of
+//! every node reached, from which the paths themselves can be rebuilt.
+//! Edge costs are computed from the edge weight by a closure. All
+//! three work on any graph implementing the `visit` traits, so also
+//! on adapters such as `Reversed(&g)`.
+//!
+use std::{collections::{BinaryHeap, HashMap, hash_map::Entry}, error, fmt, hash::Hash};
+use crate::data::graph::NodeIx;
+use crate::visit::{EdgeReference, GraphProp, IntoEdgeReferences, IntoEdges, NodeCount, VisitMap, Visitable};
+use super::{Measure, MinScored};
+
+/// The result of a shortest path search from a single start node
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths<K, N: Eq + Hash = NodeIx> {
+    pub start: N,
+    /// The cost of the cheapest known path to each reached node
+    pub distance: HashMap<N, K>,
+    /// The node preceding each reached node on that path
+    pub predecessor: HashMap<N, N>,
+}
+
+impl<K, N: Copy + Eq + Hash> Paths<K, N> {
+
+    fn new(start: N, zero: K) -> Self {
+        let mut distance = HashMap::new();
+        distance.insert(start, zero);
+        Self { start, distance, predecessor: HashMap::new() }
+    }
+
+    /// The nodes on the cheapest path from `start` to `target`,
+    /// inclusive, or `None` if `target` was not reached
+    pub fn path_to(&self, target: N) -> Option<Vec<N>> {
+        if !self.distance.contains_key(&target) {
+            return None;
+        }
+        let mut path = vec![target];
+        let mut curr = target;
+        while curr != self.start {
+            curr = *self.predecessor.get(&curr)?;
+            path.push(curr);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Returned by `bellman_ford` when a negative cycle is reachable
+/// from the start node, making shortest paths undefined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeCycle;
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("graph contains a negative cycle")
+    }
+}
+
+impl error::Error for NegativeCycle {}
+
+/// Dijkstra's algorithm. Finds the cheapest path from `start` to
+/// every reachable node, stopping early once `goal` is settled if one
+/// is given. Edge costs must be non-negative.
+pub fn dijkstra<G, F, K>(
+    g: G,
+    start: G::NodeId,
+    goal: Option<G::NodeId>,
+    mut edge_cost: F) -> Paths<K, G::NodeId>
+where
+    G: IntoEdges + Visitable,
+    F: FnMut(&G::EdgeWeight) -> K,
+    K: Measure,
+{
+    let mut settled = g.visit_map();
+    let mut paths = Paths::new(start, K::default());
+    let mut heap = BinaryHeap::new();
+    heap.push(MinScored(K::default(), start));
+    while let Some(MinScored(cost, node)) = heap.pop() {
+        if !settled.visit(node) {
+            continue;
+        }
+        if goal == Some(node) {
+            break;
+        }
+        for edge in g.edges(node) {
+            let next = edge.target();
+            if settled.is_visited(&next) {
+                continue;
+            }
+            let next_cost = cost + edge_cost(edge.weight());
+            if relax(&mut paths, node, next, next_cost) {
+                heap.push(MinScored(next_cost, next));
+            }
+        }
+    }
+    paths
+}
+
+/// The Bellman-Ford algorithm. Slower than `dijkstra`, but allows
+/// negative edge costs. On an undirected graph every edge is relaxed
+/// both ways, so any negative edge forms a negative cycle.
+pub fn bellman_ford<G, F, K>(
+    g: G,
+    start: G::NodeId,
+    mut edge_cost: F) -> Result<Paths<K, G::NodeId>, NegativeCycle>
+where
+    G: IntoEdgeReferences + NodeCount + GraphProp,
+    F: FnMut(&G::EdgeWeight) -> K,
+    K: Measure,
+{
+    let mut paths = Paths::new(start, K::default());
+    let edges = g.edge_references()
+        .map(|e| (e.source(), e.target(), edge_cost(e.weight())))
+        .collect::<Vec<_>>();
+    let relax_all = |paths: &mut Paths<K, G::NodeId>| {
+        let mut changed = false;
+        for &(a, b, cost) in &edges {
+            changed |= relax_from(paths, a, b, cost);
+            if !g.is_directed() {
+                changed |= relax_from(paths, b, a, cost);
+            }
+        }
+        changed
+    };
+    for _ in 1..g.node_count() {
+        if !relax_all(&mut paths) {
+            return Ok(paths);
+        }
+    }
+    if relax_all(&mut paths) {
+        return Err(NegativeCycle);
+    }
+    Ok(paths)
+}
+
+/// The A* algorithm. Finds the cheapest path from `start` to `goal`,
+/// guided by `estimate_cost`, which must never overestimate the
+/// remaining cost to `goal` from a node.
+pub fn astar<G, F, H, K>(
+    g: G,
+    start: G::NodeId,
+    goal: G::NodeId,
+    mut edge_cost: F,
+    mut estimate_cost: H) -> Paths<K, G::NodeId>
+where
+    G: IntoEdges,
+    F: FnMut(&G::EdgeWeight) -> K,
+    H: FnMut(G::NodeId) -> K,
+    K: Measure,
+{
+    let mut paths = Paths::new(start, K::default());
+    let mut estimates = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    heap.push(MinScored(estimate_cost(start), start));
+    while let Some(MinScored(estimate, node)) = heap.pop() {
+        if node == goal {
+            break;
+        }
+        // Skip entries superseded by a cheaper path to `node`
+        match estimates.entry(node) {
+            Entry::Occupied(mut o) => {
+                if *o.get() <= estimate {
+                    continue;
+                }
+                o.insert(estimate);
+            }
+            Entry::Vacant(v) => { v.insert(estimate); }
+        }
+        let cost = paths.distance[&node];
+        for edge in g.edges(node) {
+            let next = edge.target();
+            let next_cost = cost + edge_cost(edge.weight());
+            if relax(&mut paths, node, next, next_cost) {
+                heap.push(MinScored(next_cost + estimate_cost(next), next));
+            }
+        }
+    }
+    paths
+}
+
+/// Record `cost` as the distance to `next` via `node` if it is
+/// cheaper than any known path. Returns whether it was.
+fn relax<K: Measure, N: Copy + Eq + Hash>(
+    paths: &mut Paths<K, N>,
+    node: N,
+    next: N,
+    cost: K) -> bool
+{
+    match paths.distance.entry(next) {
+        Entry::Occupied(o) if *o.get() <= cost => return false,
+        Entry::Occupied(mut o) => { o.insert(cost); }
+        Entry::Vacant(v) => { v.insert(cost); }
+    }
+    paths.predecessor.insert(next, node);
+    true
+}
+
+/// Relax the edge `a -> b` if `a` has been reached
+fn relax_from<K: Measure, N: Copy + Eq + Hash>(
+    paths: &mut Paths<K, N>,
+    a: N,
+    b: N,
+    cost: K) -> bool
+{
+    match paths.distance.get(&a) {
+        Some(&d) => relax(paths, a, b, d + cost),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::data::graph::Graph;
+
+    fn grid() -> (Graph<(i32, i32), f64>, Vec<NodeIx>) {
+        let mut g = Graph::new_undirected();
+        let n = (0..9).map(|i| g.add((i % 3, i / 3))).collect::<Vec<_>>();
+        for i in 0..9 {
+            if i % 3 < 2 { g.add_edge(n[i], n[i + 1], 1.0); }
+            if i < 6 { g.add_edge(n[i], n[i + 3], 1.5); }
+        }
+        (g, n)
+    }
+
+    #[test]
+    fn dijkstra_ints() {
+        let mut g = Graph::<&str, u32>::new_directed();
+        let a = g.add("a");
+        let b = g.add("b");
+        let c = g.add("c");
+        let d = g.add("d");
+        g.add_edge(a, b, 7);
+        g.add_edge(a, c, 2);
+        g.add_edge(c, b, 3);
+        g.add_edge(b, d, 1);
+        let paths = dijkstra(&g, a, None, |w| *w);
+        assert_eq!(paths.distance[&b], 5);
+        assert_eq!(paths.distance[&d], 6);
+        assert_eq!(paths.path_to(d), Some(vec![a, c, b, d]));
+        let back = dijkstra(&g, d, None, |w| *w);
+        assert_eq!(back.path_to(a), None);
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let (g, n) = grid();
+        let goal = *g.weight(n[8]).unwrap();
+        let paths = astar(&g, n[0], n[8], |w| *w, |x| {
+            let (px, py) = *g.weight(x).unwrap();
+            ((goal.0 - px).abs() + (goal.1 - py).abs()) as f64
+        });
+        let exact = dijkstra(&g, n[0], Some(n[8]), |w| *w);
+        assert_eq!(paths.distance[&n[8]], 5.0);
+        assert_eq!(paths.distance[&n[8]], exact.distance[&n[8]]);
+        assert_eq!(paths.path_to(n[8]).map(|p| p.len()), Some(5));
+    }
+
+    #[test]
+    fn dijkstra_through_adapters() {
+        use crate::visit::{NodeFiltered, Reversed};
+        let mut g = Graph::<&str, u32>::new_directed();
+        let a = g.add("a");
+        let b = g.add("b");
+        let c = g.add("c");
+        g.add_edge(a, b, 1);
+        g.add_edge(b, c, 1);
+        g.add_edge(a, c, 5);
+        let back = dijkstra(Reversed(&g), c, None, |w| *w);
+        assert_eq!(back.distance[&a], 2);
+        assert_eq!(back.path_to(a), Some(vec![c, b, a]));
+        let no_b = NodeFiltered(&g, |n| n != b);
+        let paths = dijkstra(&no_b, a, None, |w| *w);
+        assert_eq!(paths.distance[&c], 5);
+        assert!(!paths.distance.contains_key(&b));
+        assert_eq!(bellman_ford(&no_b, a, |w| *w).unwrap().distance[&c], 5);
+    }
+
+    #[test]
+    fn bellman_ford_negative() {
+        let mut g = Graph::<(), i64>::new_directed();
+        let n = (0..4).map(|_| g.add(())).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], 4);
+        g.add_edge(n[0], n[2], 5);
+        g.add_edge(n[2], n[1], -3);
+        g.add_edge(n[1], n[3], 2);
+        let paths = bellman_ford(&g, n[0], |w| *w).unwrap();
+        assert_eq!(paths.distance[&n[1]], 2);
+        assert_eq!(paths.distance[&n[3]], 4);
+        assert_eq!(paths.path_to(n[3]), Some(vec![n[0], n[2], n[1], n[3]]));
+        g.add_edge(n[3], n[2], -1);
+        assert_eq!(bellman_ford(&g, n[0], |w| *w), Err(NegativeCycle));
+    }
+}
@@ -0,0 +1,287 @@
+//! Topological ordering and cycle detection for directed graphs,
+//! both as a one-off sort and kept up to date incrementally as
+//! edges are added.
+//!
+use std::{error, fmt};
+use crate::data::graph::{DefaultIx, Direction, EdgeIx, Graph, IndexType, NodeIx};
+use crate::error::{RecolError, RecolResult};
+use crate::visit::{DfsEvent, DfsEvents};
+
+/// A cycle was found, passing through the given node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle<Ix = DefaultIx>(pub NodeIx<Ix>);
+
+impl<Ix: IndexType> Cycle<Ix> {
+
+    pub fn node_id(&self) -> NodeIx<Ix> {
+        self.0
+    }
+}
+
+impl<Ix: IndexType> fmt::Display for Cycle<Ix> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph contains a cycle through node {}", self.0)
+    }
+}
+
+impl<Ix: IndexType> error::Error for Cycle<Ix> {}
+
+impl<Ix: IndexType> From<Cycle<Ix>> for RecolError {
+    fn from(c: Cycle<Ix>) -> Self {
+        Self::Cycle(NodeIx::new(c.0.index()))
+    }
+}
+
+/// Order the nodes of a directed graph so every edge points from an
+/// earlier node to a later one. Fails with a node on a cycle if no
+/// such order exists. Undirected graphs aren't supported: each edge
+/// would read as a cycle, so any edge fails in release builds, and
+/// debug builds panic.
+pub fn toposort<N, E, Ix>(g: &Graph<N, E, Ix>) -> Result<Vec<NodeIx<Ix>>, Cycle<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    debug_assert!(g.is_directed(), "toposort: undirected graph");
+    let mut order = Vec::with_capacity(g.node_count());
+    let mut dfs: Option<DfsEvents<Ix>> = None;
+    for root in g.node_indices() {
+        let dfs = match dfs.as_mut() {
+            Some(dfs) if dfs.is_discovered(root) => continue,
+            Some(dfs) => { dfs.move_to(g, root); dfs }
+            None => dfs.get_or_insert_with(|| DfsEvents::new(g, root)),
+        };
+        while let Some(event) = dfs.next(g) {
+            match event {
+                DfsEvent::BackEdge(_, v) => return Err(Cycle(v)),
+                DfsEvent::Finish(n, _) => order.push(n),
+                _ => {}
+            }
+        }
+    }
+    order.reverse();
+    Ok(order)
+}
+
+/// Whether a directed graph contains a cycle, self loops included.
+/// As with `toposort`, an undirected graph with any edge reads as
+/// cyclic in release builds, and debug builds panic.
+pub fn is_cyclic_directed<N, E, Ix>(g: &Graph<N, E, Ix>) -> bool
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    toposort(g).is_err()
+}
+
+/// A directed graph which is kept acyclic, along with a topological
+/// order of its nodes which is repaired on each insertion rather than
+/// recomputed (the Pearce-Kelly algorithm). Edges which would close a
+/// cycle are rejected.
+#[derive(Debug)]
+pub struct Acyclic<N, E, Ix = DefaultIx>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    graph: Graph<N, E, Ix>,
+    /// Position of each node in `order`
+    position: Vec<usize>,
+    order: Vec<NodeIx<Ix>>,
+}
+
+impl<N, E, Ix> Default for Acyclic<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            graph: Graph::new_directed(),
+            position: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl<N, E, Ix> Acyclic<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap an existing directed graph, failing if it has a cycle
+    pub fn try_from_graph(graph: Graph<N, E, Ix>) -> Result<Self, Cycle<Ix>> {
+        let order = toposort(&graph)?;
+        let mut position = vec![0; order.len()];
+        for (pos, n) in order.iter().enumerate() {
+            position[n.index()] = pos;
+        }
+        Ok(Self { graph, position, order })
+    }
+
+    /// Add a node, placed last in the topological order
+    pub fn add(&mut self, weight: N) -> NodeIx<Ix> {
+        let n = self.graph.add(weight);
+        self.position.push(self.order.len());
+        self.order.push(n);
+        n
+    }
+
+    /// Add the edge `a -> b` unless it would close a cycle, failing
+    /// with `RecolError::Cycle` if it would, or `NodeNotFound` if
+    /// either end is missing. On failure the graph is left unchanged.
+    pub fn try_add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
+        for n in [a, b] {
+            if n.index() >= self.position.len() {
+                return Err(RecolError::node_not_found(n));
+            }
+        }
+        if a == b {
+            return Err(Cycle(a).into());
+        }
+        let (lower, upper) = (self.position[b.index()], self.position[a.index()]);
+        if lower < upper {
+            let forward = self.reach(b, Direction::Outgoing, |p| p <= upper, Some(a))
+                .ok_or(Cycle(a))?;
+            let backward = self.reach(a, Direction::Incoming, |p| p >= lower, None)
+                .unwrap_or_default();
+            self.reorder(backward, forward);
+        }
+        self.graph.try_add_edge(a, b, weight)
+    }
+
+    pub fn remove_edge(&mut self, e: EdgeIx<Ix>) -> Option<E> {
+        self.graph.remove_edge(e)
+    }
+
+    /// The nodes in topological order
+    pub fn order(&self) -> &[NodeIx<Ix>] {
+        &self.order
+    }
+
+    /// The position of `n` in the topological order
+    pub fn position(&self, n: NodeIx<Ix>) -> Option<usize> {
+        self.position.get(n.index()).copied()
+    }
+
+    pub fn graph(&self) -> &Graph<N, E, Ix> {
+        &self.graph
+    }
+
+    pub fn into_inner(self) -> Graph<N, E, Ix> {
+        self.graph
+    }
+
+    /// Collect the nodes reachable from `start` in direction `dir`
+    /// whose position satisfies `within`. Returns `None` if the walk
+    /// runs into `forbidden`.
+    fn reach<P>(
+        &self,
+        start: NodeIx<Ix>,
+        dir: Direction,
+        within: P,
+        forbidden: Option<NodeIx<Ix>>) -> Option<Vec<NodeIx<Ix>>>
+    where
+        P: Fn(usize) -> bool,
+    {
+        let mut seen = vec![false; self.graph.node_count()];
+        let mut stack = vec![start];
+        let mut found = Vec::new();
+        seen[start.index()] = true;
+        while let Some(n) = stack.pop() {
+            found.push(n);
+            for next in self.graph.neighbors_directed(n, dir) {
+                if Some(next) == forbidden {
+                    return None;
+                }
+                let pos = self.position[next.index()];
+                if within(pos) && !std::mem::replace(&mut seen[next.index()], true) {
+                    stack.push(next);
+                }
+            }
+        }
+        Some(found)
+    }
+
+    /// Move every node in `backward` before every node in `forward`,
+    /// reusing the positions the two sets already occupy
+    fn reorder(&mut self, mut backward: Vec<NodeIx<Ix>>, mut forward: Vec<NodeIx<Ix>>) {
+        backward.sort_by_key(|n| self.position[n.index()]);
+        forward.sort_by_key(|n| self.position[n.index()]);
+        let mut slots = backward.iter().chain(&forward)
+            .map(|n| self.position[n.index()])
+            .collect::<Vec<_>>();
+        slots.sort_unstable();
+        for (n, pos) in backward.into_iter().chain(forward).zip(slots) {
+            self.position[n.index()] = pos;
+            self.order[pos] = n;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn toposort_orders_edges() {
+        let mut g = Graph::<&str, ()>::new_directed();
+        let shirt = g.add("shirt");
+        let tie = g.add("tie");
+        let jacket = g.add("jacket");
+        let belt = g.add("belt");
+        g.add_edge(tie, jacket, ());
+        g.add_edge(shirt, tie, ());
+        g.add_edge(belt, jacket, ());
+        g.add_edge(shirt, belt, ());
+        let order = toposort(&g).unwrap();
+        let pos = |n| order.iter().position(|&x| x == n).unwrap();
+        assert!(pos(shirt) < pos(tie) && pos(tie) < pos(jacket));
+        assert!(pos(belt) < pos(jacket));
+        assert!(!is_cyclic_directed(&g));
+        g.add_edge(jacket, shirt, ());
+        assert!(matches!(toposort(&g), Err(Cycle(_))));
+    }
+
+    #[test]
+    fn self_loop_is_cycle() {
+        let mut g = Graph::<(), ()>::new_directed();
+        let a = g.add(());
+        g.add_edge(a, a, ());
+        assert_eq!(toposort(&g), Err(Cycle(a)));
+    }
+
+    #[test]
+    fn acyclic_rejects_cycles() {
+        let mut dag = Acyclic::<usize, ()>::new();
+        let n = (0..5).map(|i| dag.add(i)).collect::<Vec<_>>();
+        dag.try_add_edge(n[3], n[1], ()).unwrap();
+        dag.try_add_edge(n[4], n[3], ()).unwrap();
+        dag.try_add_edge(n[1], n[0], ()).unwrap();
+        dag.try_add_edge(n[2], n[4], ()).unwrap();
+        let cycle = |r: RecolResult<_>| match r {
+            Err(RecolError::Cycle(c)) => Some(c.index()),
+            _ => None,
+        };
+        assert_eq!(cycle(dag.try_add_edge(n[0], n[2], ())), Some(0));
+        assert_eq!(cycle(dag.try_add_edge(n[0], n[0], ())), Some(0));
+        assert!(matches!(dag.try_add_edge(n[0], NodeIx::new(9), ()), Err(RecolError::NodeNotFound(_))));
+        assert_eq!(dag.graph().edge_count(), 4);
+        for e in dag.graph().edge_indices() {
+            let (a, b) = dag.graph().edge_endpoints(e).unwrap();
+            assert!(dag.position(a) < dag.position(b));
+        }
+        assert_eq!(dag.order(), &[n[2], n[4], n[3], n[1], n[0]][..]);
+    }
+}
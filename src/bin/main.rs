@@ -1,7 +1,6 @@
 use std::{time, thread, sync::mpsc};
-use recollection::{RecolError, RecolResult,
-    data::{Graph, Node, Edge},
-    fs::{cat, copy, rm, touch, mkdir}
+use recollection::{RecolResult,
+    fs::{cat, touch, mkdir}
 };
 
 fn main() -> RecolResult<()> {
@@ -18,10 +17,11 @@ fn main() -> RecolResult<()> {
     Ok(())
 }
 
+#[allow(dead_code)]
 fn tests() -> RecolResult<()> {
-    let r = cat("./Cargo.toml")?;
-    let fi = touch("./test.toml")?;
-    let td = mkdir("./testdir")?;
+    let _r = cat("./Cargo.toml")?;
+    let _fi = touch("./test.toml")?;
+    let _td = mkdir("./testdir")?;
     // println!("{}", r);
     Ok(())
 }
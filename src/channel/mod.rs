@@ -2,10 +2,9 @@
 //! and job execution, related tasks.
 //! Base on code from the Rust programming language book v2
 //!
-use crate::{RecolError, RecolResult};
-use std::{sync::{self, Arc, Mutex, mpsc}, thread};
+use std::{sync::{Arc, Mutex, mpsc}, thread};
 
-pub fn sender() -> () {
+pub fn sender() {
 }
 
 enum Op {
@@ -13,10 +12,6 @@ enum Op {
     Terminate,
 }
 
-trait OpLike {
-
-}
-
 pub struct Worker {
     id: usize,
     thread: Option<thread::JoinHandle<()>>, 
@@ -43,7 +38,7 @@ impl Worker {
     }
 }
 
-trait ActionWrap {
+pub trait ActionWrap {
     fn call_box(self: Box<Self>);
 }
 impl<F: FnOnce()> ActionWrap for F {
@@ -82,14 +77,6 @@ impl ActionQueue {
 impl Drop for ActionQueue {
     fn drop(&mut self) {
         println!("Sending terminate sig to all workers");
-        self.workers.iter()
-            .inspect(|_| self.sender.send(Op::Terminate).unwrap());
-        for worker in &mut self.workers {
-                if let Some(thread) = worker.thread.take() {
-                    thread.join();
-                }
-        }
-
         for worker in &mut self.workers {
             self.sender.send(Op::Terminate).unwrap();
             println!("Shutting down worker {}", worker.id);
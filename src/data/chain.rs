@@ -2,8 +2,8 @@
 //! greatly inspired by learning rust with entirely too many linked list (rust-unofficial on gh)
 //!
 //!
-// use serde::{Serialize, Deserialize};
-use std::{fmt::{self, Write}, ops, rc::Rc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, rc::Rc};
 
 #[derive(Debug, Clone)]
 pub struct Chain<T> {
@@ -73,6 +73,12 @@ impl<T> Link<T> {
 }
 
 
+impl<T> Default for Chain<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Chain<T> {
 
     pub fn new() -> Self {
@@ -97,13 +103,11 @@ impl<T> Chain<T> {
     }
 
     pub fn len(&self) -> usize {
-        let mut len = 0;
-        self.head.as_ref()
-            .and_then(|n| {
-                len += 1;
-                n.next.clone()
-            });
-        return len;
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
     }
 
     /// Get a mutable reference to the chain's head.
@@ -132,14 +136,29 @@ impl<'a, T> Iterator for ChainIter<'a, T> {
 impl<T> Drop for Chain<T> {
     fn drop(&mut self) {
         let mut head = self.head.take();
-        while let Some(mut node) = head {
+        while let Some(node) = head {
             match Rc::try_unwrap(node) {
                 Ok(mut n) => { head = n.next.take(); }
-                Err(e) => break,
+                Err(_) => break,
             }
         }
     }
 }
+/// Serialized as a sequence, head first
+impl<T: Serialize> Serialize for Chain<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Chain<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut chain = Chain::new();
+        for data in Vec::<T>::deserialize(deserializer)?.into_iter().rev() {
+            chain.prepend(data);
+        }
+        Ok(chain)
+    }
+}
 /* impl<'a, T> IntoIterator for Chain<T> 
 where
     T: Sized + Clone
@@ -174,7 +193,7 @@ mod test {
 
     #[test]
     fn push_ok() -> RecolResult<()> {
-        let mut chain = Chain::<usize>::new();
+        let chain = Chain::<usize>::new();
         /* chain.push(4);
         chain.push(8);
         chain.push(9); */
@@ -182,9 +201,33 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn len_ok() -> RecolResult<()> {
+        let mut chain = Chain::init(3);
+        chain.prepend(2);
+        chain.prepend(1);
+        assert_eq!(chain.len(), 3);
+        assert!(!chain.is_empty());
+        assert_eq!(chain.tail().tail().len(), 1);
+        assert!(Chain::<usize>::default().is_empty());
+        Ok(())
+    }
+
     #[test]
     fn iter_ok() -> RecolResult<()> {
         Ok(())
 
     }
+
+    #[test]
+    fn serde_ok() -> RecolResult<()> {
+        let mut chain = Chain::init(3);
+        chain.prepend(2);
+        chain.prepend(1);
+        let json = serde_json::to_string(&chain).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: Chain<usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        Ok(())
+    }
 }
@@ -0,0 +1,387 @@
+//! A compressed sparse row graph: every node's outgoing edges sit
+//! in one contiguous slice, sorted by target. Built once and then
+//! only read, it trades `Graph`'s cheap mutation for cache friendly
+//! iteration and binary search edge lookup.
+//!
+use std::{fmt, iter, ops::Range, slice};
+use crate::data::graph::{DefaultIx, EdgeIx, Graph, IndexType, NodeIndices, NodeIx};
+use crate::error::{RecolError, RecolResult};
+use crate::visit::{
+    Data, EdgeReference, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoNeighbors,
+    IntoNodeIdentifiers, NodeCount, NodeIndexable, Visitable,
+};
+
+/// A read-only graph in compressed sparse row form. An edge's index
+/// is its position in the concatenated rows. Undirected edges are
+/// stored in the rows of both their ends, so have two indices, and
+/// `edge_references` yields each once, from its smaller end.
+///
+/// Only outgoing edges are indexed, so `Csr` does not implement
+/// `IntoNeighborsDirected`, which `Bfs` and `Dfs` walk by.
+#[derive(Debug, Clone)]
+pub struct Csr<N, E, Ix = DefaultIx> {
+    nodes: Vec<N>,
+    /// Row `a` is `columns[rows[a]..rows[a + 1]]`
+    rows: Vec<usize>,
+    columns: Vec<NodeIx<Ix>>,
+    weights: Vec<E>,
+    edge_count: usize,
+    directed: bool,
+}
+
+impl<N, E, Ix> Csr<N, E, Ix>
+where
+    E: Clone,
+    Ix: IndexType,
+{
+
+    /// Build from node weights and `(source, target, weight)` edges,
+    /// in any order. Fails if an edge names a missing node.
+    pub fn from_edges<I>(directed: bool, nodes: Vec<N>, edges: I) -> RecolResult<Self>
+    where
+        I: IntoIterator<Item = (NodeIx<Ix>, NodeIx<Ix>, E)>,
+    {
+        let n = nodes.len();
+        let mut entries = Vec::new();
+        let mut edge_count = 0;
+        for (a, b, weight) in edges {
+            for x in [a, b] {
+                if x.index() >= n {
+                    return Err(RecolError::node_not_found(x));
+                }
+            }
+            if !directed && a != b {
+                entries.push((b, a, weight.clone()));
+            }
+            entries.push((a, b, weight));
+            edge_count += 1;
+        }
+        if n >= Ix::end().index() || entries.len() >= Ix::end().index() {
+            return Err(RecolError::IndexOverflow);
+        }
+        entries.sort_by_key(|&(a, b, _)| (a, b));
+
+        let mut rows = vec![0; n + 1];
+        for &(a, _, _) in &entries {
+            rows[a.index() + 1] += 1;
+        }
+        for i in 0..n {
+            rows[i + 1] += rows[i];
+        }
+        let (columns, weights) = entries.into_iter().map(|(_, b, w)| (b, w)).unzip();
+        Ok(Self { nodes, rows, columns, weights, edge_count, directed })
+    }
+}
+
+impl<N, E, Ix: IndexType> Csr<N, E, Ix> {
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn edge_count(&self) -> usize { self.edge_count }
+
+    pub fn weight(&self, a: NodeIx<Ix>) -> Option<&N> {
+        self.nodes.get(a.index())
+    }
+
+    pub fn weight_mut(&mut self, a: NodeIx<Ix>) -> Option<&mut N> {
+        self.nodes.get_mut(a.index())
+    }
+
+    pub fn edge_weight(&self, e: EdgeIx<Ix>) -> Option<&E> {
+        self.weights.get(e.index())
+    }
+
+    fn row(&self, a: NodeIx<Ix>) -> Range<usize> {
+        match self.rows.get(a.index() + 1) {
+            Some(&end) => self.rows[a.index()]..end,
+            None => 0..0,
+        }
+    }
+
+    /// The targets of the edges leaving `a`, sorted, repeated once
+    /// per parallel edge
+    pub fn neighbors_slice(&self, a: NodeIx<Ix>) -> &[NodeIx<Ix>] {
+        &self.columns[self.row(a)]
+    }
+
+    /// The weights of the edges leaving `a`, in the order of
+    /// `neighbors_slice`
+    pub fn edge_weights(&self, a: NodeIx<Ix>) -> &[E] {
+        &self.weights[self.row(a)]
+    }
+
+    /// The successors of `a` if directed, all its neighbors if not
+    pub fn neighbors(&self, a: NodeIx<Ix>) -> iter::Copied<slice::Iter<'_, NodeIx<Ix>>> {
+        self.neighbors_slice(a).iter().copied()
+    }
+
+    /// The first edge from `a` to `b`, by binary search
+    pub fn get_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> Option<EdgeIx<Ix>> {
+        let row = self.row(a);
+        let slice = &self.columns[row.clone()];
+        let i = slice.partition_point(|&n| n < b);
+        (slice.get(i) == Some(&b)).then(|| EdgeIx::new(row.start + i))
+    }
+
+    pub fn contains_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> bool {
+        self.neighbors_slice(a).binary_search(&b).is_ok()
+    }
+
+    /// The edges leaving `a`, sorted by target. Undirected edges are
+    /// oriented so `a` is the source.
+    pub fn edges(&self, a: NodeIx<Ix>) -> CsrEdges<'_, E, Ix> {
+        let row = self.row(a);
+        CsrEdges {
+            source: a,
+            ids: row.clone(),
+            columns: self.columns[row.clone()].iter(),
+            weights: self.weights[row].iter(),
+        }
+    }
+
+    pub fn node_indices(&self) -> NodeIndices<Ix> {
+        (0..self.nodes.len()).map(NodeIx::new)
+    }
+
+    pub fn edge_references(&self) -> CsrEdgeReferences<'_, N, E, Ix> {
+        CsrEdgeReferences { g: self, node: 0, edges: None }
+    }
+}
+
+/// Copies `g`, keeping node indices. Parallel edges are kept.
+impl<N, E, Ix> From<&Graph<N, E, Ix>> for Csr<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    fn from(g: &Graph<N, E, Ix>) -> Self {
+        let nodes = (0..g.node_count())
+            .map(|i| g.weight(NodeIx::new(i)).unwrap().clone())
+            .collect();
+        let edges = g.edge_references()
+            .map(|e| (e.source(), e.target(), e.weight().clone()));
+        Self::from_edges(g.is_directed(), nodes, edges)
+            .expect("a Graph's edges join its own nodes")
+    }
+}
+
+/// A borrowed edge of a `Csr`
+pub struct CsrEdgeRef<'a, E, Ix = DefaultIx> {
+    id: EdgeIx<Ix>,
+    source: NodeIx<Ix>,
+    target: NodeIx<Ix>,
+    weight: &'a E,
+}
+
+impl<'a, E, Ix: Copy> Clone for CsrEdgeRef<'a, E, Ix> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, E, Ix: Copy> Copy for CsrEdgeRef<'a, E, Ix> {}
+
+impl<'a, E: fmt::Debug, Ix: IndexType> fmt::Debug for CsrEdgeRef<'a, E, Ix> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsrEdgeRef")
+            .field("id", &self.id)
+            .field("source", &self.source)
+            .field("target", &self.target)
+            .field("weight", self.weight)
+            .finish()
+    }
+}
+
+impl<'a, E, Ix: IndexType> EdgeReference for CsrEdgeRef<'a, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+    type Weight = E;
+
+    fn source(&self) -> NodeIx<Ix> { self.source }
+
+    fn target(&self) -> NodeIx<Ix> { self.target }
+
+    fn weight(&self) -> &E { self.weight }
+
+    fn id(&self) -> EdgeIx<Ix> { self.id }
+}
+
+/// The edges of one row
+#[derive(Debug, Clone)]
+pub struct CsrEdges<'a, E, Ix = DefaultIx> {
+    source: NodeIx<Ix>,
+    ids: Range<usize>,
+    columns: slice::Iter<'a, NodeIx<Ix>>,
+    weights: slice::Iter<'a, E>,
+}
+
+impl<'a, E, Ix: IndexType> Iterator for CsrEdges<'a, E, Ix> {
+    type Item = CsrEdgeRef<'a, E, Ix>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(CsrEdgeRef {
+            id: EdgeIx::new(self.ids.next()?),
+            source: self.source,
+            target: *self.columns.next()?,
+            weight: self.weights.next()?,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+/// Every edge, row by row
+pub struct CsrEdgeReferences<'a, N, E, Ix = DefaultIx> {
+    g: &'a Csr<N, E, Ix>,
+    node: usize,
+    edges: Option<CsrEdges<'a, E, Ix>>,
+}
+
+impl<'a, N, E, Ix: IndexType> Iterator for CsrEdgeReferences<'a, N, E, Ix> {
+    type Item = CsrEdgeRef<'a, E, Ix>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(edges) = &mut self.edges {
+                let directed = self.g.directed;
+                if let Some(e) = edges.find(|e| directed || e.source <= e.target) {
+                    return Some(e);
+                }
+            }
+            if self.node >= self.g.nodes.len() {
+                return None;
+            }
+            self.edges = Some(self.g.edges(NodeIx::new(self.node)));
+            self.node += 1;
+        }
+    }
+}
+
+impl<N, E, Ix: IndexType> GraphBase for Csr<N, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+}
+
+impl<N, E, Ix: IndexType> Data for Csr<N, E, Ix> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+
+impl<N, E, Ix: IndexType> GraphProp for Csr<N, E, Ix> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<N, E, Ix: IndexType> NodeCount for Csr<N, E, Ix> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<N, E, Ix: IndexType> NodeIndexable for Csr<N, E, Ix> {
+    fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn to_index(&self, n: NodeIx<Ix>) -> usize {
+        n.index()
+    }
+
+    fn from_index(&self, i: usize) -> NodeIx<Ix> {
+        NodeIx::new(i)
+    }
+}
+
+impl<N, E, Ix: IndexType> Visitable for Csr<N, E, Ix> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.nodes.len()]
+    }
+
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.nodes.len(), false);
+    }
+}
+
+impl<'a, N, E, Ix: IndexType> IntoNeighbors for &'a Csr<N, E, Ix> {
+    type Neighbors = iter::Copied<slice::Iter<'a, NodeIx<Ix>>>;
+
+    fn neighbors(self, n: NodeIx<Ix>) -> Self::Neighbors {
+        Csr::neighbors(self, n)
+    }
+}
+
+impl<N, E, Ix: IndexType> IntoNodeIdentifiers for &Csr<N, E, Ix> {
+    type NodeIdentifiers = NodeIndices<Ix>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        (0..self.nodes.len()).map(NodeIx::new)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType> IntoEdgeReferences for &'a Csr<N, E, Ix> {
+    type EdgeRef = CsrEdgeRef<'a, E, Ix>;
+    type EdgeReferences = CsrEdgeReferences<'a, N, E, Ix>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        Csr::edge_references(self)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType> IntoEdges for &'a Csr<N, E, Ix> {
+    type Edges = CsrEdges<'a, E, Ix>;
+
+    fn edges(self, n: NodeIx<Ix>) -> Self::Edges {
+        Csr::edges(self, n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::algo::dijkstra;
+
+    #[test]
+    fn csr_rows_are_sorted() {
+        let n = |i| NodeIx::<u32>::new(i);
+        let edges = vec![(n(0), n(2), 'a'), (n(0), n(1), 'b'), (n(2), n(0), 'c'), (n(0), n(2), 'd')];
+        let g = Csr::from_edges(true, vec![(); 3], edges).unwrap();
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!(g.neighbors_slice(n(0)), &[n(1), n(2), n(2)]);
+        assert_eq!(g.edge_weights(n(0)), &['b', 'a', 'd']);
+        assert!(g.neighbors_slice(n(1)).is_empty());
+        assert!(g.contains_edge(n(2), n(0)));
+        assert!(!g.contains_edge(n(0), n(0)));
+        assert_eq!(g.get_edge(n(0), n(2)).and_then(|e| g.edge_weight(e)), Some(&'a'));
+        assert_eq!(g.edge_references().count(), 4);
+
+        let bad = Csr::<(), (), u32>::from_edges(true, vec![()], vec![(n(0), n(1), ())]);
+        assert!(matches!(bad, Err(RecolError::NodeNotFound(_))));
+    }
+
+    #[test]
+    fn csr_from_graph() {
+        let mut g = Graph::<(), u32>::new_undirected();
+        let n = (0..4).map(|_| g.add(())).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], 4);
+        g.add_edge(n[2], n[1], 1);
+        g.add_edge(n[0], n[2], 7);
+        g.add_edge(n[3], n[2], 1);
+        g.add_edge(n[3], n[3], 9);
+        let csr = Csr::from(&g);
+        assert_eq!(csr.edge_count(), 5);
+        assert_eq!(csr.edge_references().count(), 5);
+        assert!(csr.edge_references().all(|e| e.source <= e.target));
+        assert_eq!(csr.neighbors(n[2]).collect::<Vec<_>>(), vec![n[0], n[1], n[3]]);
+        assert!(csr.contains_edge(n[1], n[2]) && csr.contains_edge(n[2], n[1]));
+        let paths = dijkstra(&csr, n[0], None, |w| *w);
+        assert_eq!(paths.distance[&n[3]], 6);
+    }
+}
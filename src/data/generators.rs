@@ -0,0 +1,345 @@
+//! Builders for well known families of graphs, for tests and
+//! benchmarks. Node and edge weights are their types' `Default`, and
+//! nodes are numbered as described for each family. The random
+//! families draw from a caller supplied `Rng`, so seeding it (e.g.
+//! with `StdRng::seed_from_u64`) makes them reproducible.
+//!
+//! ```
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use recollection::data::{generators, graph::Graph};
+//!
+//! let g: Graph<(), ()> = generators::grid(3, 4, false);
+//! assert_eq!(g.edge_count(), 3 * 3 + 2 * 4);
+//! let a: Graph<(), ()> = generators::erdos_renyi(50, 0.1, true, &mut StdRng::seed_from_u64(7));
+//! let b: Graph<(), ()> = generators::erdos_renyi(50, 0.1, true, &mut StdRng::seed_from_u64(7));
+//! assert_eq!(a.edge_count(), b.edge_count());
+//! ```
+//!
+use std::collections::HashSet;
+use rand::Rng;
+use crate::data::graph::{Graph, IndexType, NodeIx};
+
+/// `n` nodes and no edges
+fn empty<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone,
+    Ix: IndexType,
+{
+    let mut g = if directed { Graph::new_directed() } else { Graph::new_undirected() };
+    for _ in 0..n {
+        g.add(N::default());
+    }
+    g
+}
+
+fn link<N, E, Ix>(g: &mut Graph<N, E, Ix>, a: usize, b: usize)
+where
+    N: Clone,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    g.add_edge(NodeIx::new(a), NodeIx::new(b), E::default());
+}
+
+/// Every pair of distinct nodes joined; in both directions if
+/// `directed`
+pub fn complete<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = empty(n, directed);
+    for a in 0..n {
+        for b in 0..n {
+            if a < b || (directed && a != b) {
+                link(&mut g, a, b);
+            }
+        }
+    }
+    g
+}
+
+/// Nodes `0..n` joined in order, `i` to `i + 1`
+pub fn path<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = empty(n, directed);
+    for i in 1..n {
+        link(&mut g, i - 1, i);
+    }
+    g
+}
+
+/// A `path` closed by an edge from its last node back to `0`. With
+/// fewer than three nodes there is no closing edge.
+pub fn cycle<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = path(n, directed);
+    if n > 2 {
+        link(&mut g, n - 1, 0);
+    }
+    g
+}
+
+/// Node `0` joined to each of the other `n - 1` nodes, pointing
+/// outwards if `directed`
+pub fn star<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = empty(n, directed);
+    for i in 1..n {
+        link(&mut g, 0, i);
+    }
+    g
+}
+
+/// A `rows` by `cols` lattice. The node in row `r` and column `c` is
+/// `r * cols + c`, joined to the nodes to its right and below it.
+pub fn grid<N, E, Ix>(rows: usize, cols: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = empty(rows * cols, directed);
+    for r in 0..rows {
+        for c in 0..cols {
+            let i = r * cols + c;
+            if c + 1 < cols {
+                link(&mut g, i, i + 1);
+            }
+            if r + 1 < rows {
+                link(&mut g, i, i + cols);
+            }
+        }
+    }
+    g
+}
+
+/// A complete binary tree of `n` nodes, numbered in breadth first
+/// order: node `i` is the parent of `2i + 1` and `2i + 2`. Edges
+/// point from parent to child.
+pub fn binary_tree<N, E, Ix>(n: usize, directed: bool) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+{
+    let mut g = empty(n, directed);
+    for i in 1..n {
+        link(&mut g, (i - 1) / 2, i);
+    }
+    g
+}
+
+/// The Erdős–Rényi `G(n, p)` graph: each pair of distinct nodes is
+/// joined independently with probability `p`, each ordered pair if
+/// `directed`. Rather than tossing a coin per pair, this samples the
+/// gaps between edges, so it takes time in proportion to the number
+/// of nodes and edges.
+pub fn erdos_renyi<N, E, Ix, R>(n: usize, p: f64, directed: bool, rng: &mut R) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+    R: Rng + ?Sized,
+{
+    if p >= 1.0 {
+        return complete(n, directed);
+    }
+    let mut g = empty(n, directed);
+    if p <= 0.0 {
+        return g;
+    }
+    // the candidates for row `v` are the nodes `v` may be joined
+    // from: all the others if directed, those below it if not
+    let row = |v: usize| if directed { n - 1 } else { v };
+    let log_miss = (1.0 - p).ln();
+    let (mut v, mut w) = (0, 0usize);
+    loop {
+        let skip = ((1.0 - rng.gen::<f64>()).ln() / log_miss).floor() as usize;
+        w = w.saturating_add(skip);
+        while v < n && w >= row(v) {
+            w -= row(v);
+            v += 1;
+        }
+        if v == n {
+            break;
+        }
+        if directed {
+            link(&mut g, v, if w < v { w } else { w + 1 });
+        } else {
+            link(&mut g, w, v);
+        }
+        w += 1;
+    }
+    g
+}
+
+/// A Barabási–Albert preferential attachment graph, undirected.
+/// Starts from a star on nodes `0..=m` centred on `0`, then joins
+/// each further node to `m` distinct earlier nodes, each chosen with
+/// probability in proportion to its degree. Panics unless
+/// `1 <= m < n`.
+pub fn barabasi_albert<N, E, Ix, R>(n: usize, m: usize, rng: &mut R) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+    R: Rng + ?Sized,
+{
+    assert!(1 <= m && m < n, "barabasi_albert needs 1 <= m < n");
+    let mut g = star(m + 1, false);
+    for _ in m + 1..n {
+        g.add(N::default());
+    }
+    // every node appears once per edge end, so a uniform pick from
+    // here is a pick in proportion to degree
+    let mut ends = (1..=m).flat_map(|i| [0, i]).collect::<Vec<_>>();
+    let mut targets = Vec::with_capacity(m);
+    for source in m + 1..n {
+        targets.clear();
+        while targets.len() < m {
+            let t = ends[rng.gen_range(0..ends.len())];
+            if !targets.contains(&t) {
+                targets.push(t);
+            }
+        }
+        for &t in &targets {
+            link(&mut g, source, t);
+            ends.push(t);
+            ends.push(source);
+        }
+    }
+    g
+}
+
+/// A Watts–Strogatz small world graph, undirected. Starts from a
+/// ring of `n` nodes each joined to the `k / 2` nearest on either
+/// side, then with probability `p` rewires each edge `(u, u + j)` to
+/// `(u, w)` for a `w` chosen uniformly among the nodes not already
+/// joined to `u`. Panics unless `k < n`.
+pub fn watts_strogatz<N, E, Ix, R>(n: usize, k: usize, p: f64, rng: &mut R) -> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone + Default,
+    Ix: IndexType,
+    R: Rng + ?Sized,
+{
+    assert!(k < n, "watts_strogatz needs k < n");
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut edges = Vec::with_capacity(n * (k / 2));
+    for j in 1..=k / 2 {
+        for u in 0..n {
+            edges.push((u, (u + j) % n));
+        }
+    }
+    let mut present = edges.iter().map(|&(a, b)| key(a, b)).collect::<HashSet<_>>();
+    let mut degree = vec![k / 2 * 2; n];
+    for edge in edges.iter_mut() {
+        let (u, v) = *edge;
+        if degree[u] + 1 >= n || !rng.gen_bool(p) {
+            continue;
+        }
+        let w = loop {
+            let w = rng.gen_range(0..n);
+            if w != u && !present.contains(&key(u, w)) {
+                break w;
+            }
+        };
+        present.remove(&key(u, v));
+        present.insert(key(u, w));
+        degree[v] -= 1;
+        degree[w] += 1;
+        *edge = (u, w);
+    }
+    let mut g = empty(n, false);
+    for (u, v) in edges {
+        link(&mut g, u, v);
+    }
+    g
+}
+
+#[cfg(test)]
+mod test {
+
+    use rand::{rngs::StdRng, SeedableRng};
+    use super::*;
+
+    type G = Graph<(), ()>;
+
+    fn simple(g: &G) -> bool {
+        let pairs = g.edge_references()
+            .map(|e| {
+                let (a, b) = (e.source().index(), e.target().index());
+                if g.is_directed() { (a, b) } else { (a.min(b), a.max(b)) }
+            })
+            .collect::<HashSet<_>>();
+        pairs.len() == g.edge_count() && pairs.iter().all(|&(a, b)| a != b)
+    }
+
+    #[test]
+    fn deterministic_families() {
+        let sizes = |g: G| (g.node_count(), g.edge_count());
+        assert_eq!(sizes(complete(5, false)), (5, 10));
+        assert_eq!(sizes(complete(5, true)), (5, 20));
+        assert_eq!(sizes(path(5, false)), (5, 4));
+        assert_eq!(sizes(cycle(5, true)), (5, 5));
+        assert_eq!(sizes(cycle(2, false)), (2, 1));
+        assert_eq!(sizes(star(5, false)), (5, 4));
+        assert_eq!(sizes(grid(3, 4, true)), (12, 17));
+        assert_eq!(sizes(binary_tree(7, true)), (7, 6));
+        assert_eq!(sizes(path(0, true)), (0, 0));
+
+        let tree: G = binary_tree(7, true);
+        let children = tree.neighbors(NodeIx::new(1)).map(|n| n.index()).collect::<HashSet<_>>();
+        assert_eq!(children, [3, 4].iter().copied().collect());
+        let g: G = grid(2, 2, false);
+        assert!(g.contains_edge(NodeIx::new(0), NodeIx::new(2)));
+    }
+
+    #[test]
+    fn erdos_renyi_is_seeded_and_simple() {
+        let edges = |seed: u64, directed| {
+            let g: G = erdos_renyi(200, 0.05, directed, &mut StdRng::seed_from_u64(seed));
+            assert!(simple(&g));
+            g.edge_references().map(|e| (e.source(), e.target())).collect::<Vec<_>>()
+        };
+        assert_eq!(edges(3, true), edges(3, true));
+        assert_ne!(edges(3, true), edges(4, true));
+        // expected counts are 0.05 of 39800 and 19900 pairs
+        assert!((1700..2300).contains(&edges(3, true).len()));
+        assert!((800..1200).contains(&edges(3, false).len()));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(erdos_renyi::<(), (), usize, _>(10, 0.0, false, &mut rng).edge_count(), 0);
+        assert_eq!(erdos_renyi::<(), (), usize, _>(10, 1.0, false, &mut rng).edge_count(), 45);
+    }
+
+    #[test]
+    fn random_models_keep_their_edge_counts() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let ba: G = barabasi_albert(100, 3, &mut rng);
+        assert_eq!(ba.edge_count(), 3 + 96 * 3);
+        assert!(simple(&ba));
+
+        let ring: G = watts_strogatz(20, 4, 0.0, &mut rng);
+        assert_eq!(ring.edge_count(), 40);
+        assert!(ring.node_indices().all(|n| ring.neighbors(n).count() == 4));
+        let ws: G = watts_strogatz(100, 6, 0.3, &mut rng);
+        assert_eq!(ws.edge_count(), 300);
+        assert!(simple(&ws));
+    }
+}
@@ -2,143 +2,134 @@
 //! for practice and personal graph data structure needs
 //! for other components of the Idle Chain
 //!
-use std::mem::size_of;
-use std::{iter, slice, vec, ops, fmt, hash};
-
-pub type NodeIx = usize;
-pub type EdgeIx = usize;
+use std::mem::{self, size_of};
+use std::{collections::HashMap, iter, ops, fmt, slice, vec};
+use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+use crate::error::{RecolError, RecolResult};
+
+pub mod dot;
+pub mod index;
+pub mod maps;
+mod serialize;
+pub mod stable;
+pub mod subgraph;
+mod visit;
+
+pub use index::{DefaultIx, EdgeIndex, EdgeIx, IndexType, NodeIndex, NodeIndices, NodeIx};
+pub use dot::Dot;
+pub use maps::{EdgeMap, NodeMap};
+pub use stable::StableGraph;
+pub use subgraph::Subgraph;
 
 #[derive( Clone)]
-pub struct Node<N: Clone> {
+pub struct Node<N: Clone, Ix = DefaultIx> {
     pub weight: N,
-    edges: EdgeLink,
+    edges: EdgeLink<Ix>,
 }
 
 #[derive(Clone)]
-pub struct Edge<E: Clone> {
+pub struct Edge<E: Clone, Ix = DefaultIx> {
     pub weight: E,
-    node: NodeLink,
-    next: EdgeLink,
+    node: NodeLink<Ix>,
+    next: EdgeLink<Ix>,
 }
 #[derive(Clone)]
-pub struct EdgeLink{
-    outgoing: EdgeIx, 
-    incoming: EdgeIx
+pub struct EdgeLink<Ix = DefaultIx> {
+    outgoing: EdgeIx<Ix>, 
+    incoming: EdgeIx<Ix>
 }
-#[derive(Clone)]
-pub struct NodeLink {
-    src: NodeIx,
-    dest: NodeIx,
+#[derive(Clone, Copy)]
+pub struct NodeLink<Ix = DefaultIx> {
+    src: NodeIx<Ix>,
+    dest: NodeIx<Ix>,
 }
-impl Default for NodeLink {
+impl<Ix: IndexType> Default for NodeLink<Ix> {
     fn default() -> Self {
         Self {
-            src: EdgeIx::max_value(),
-            dest: EdgeIx::max_value()
+            src: NodeIx::end(),
+            dest: NodeIx::end()
         }
     }
 }
-impl Default for EdgeLink {
+impl<Ix: IndexType> Default for EdgeLink<Ix> {
     fn default() -> Self {
         Self {
-            outgoing: EdgeIx::max_value(),
-            incoming: EdgeIx::max_value()
+            outgoing: EdgeIx::end(),
+            incoming: EdgeIx::end()
         }
     }
 }
-impl EdgeLink {
-    fn new(out: EdgeIx, inc: EdgeIx) -> Self {
+impl<Ix: IndexType> EdgeLink<Ix> {
+    fn new(out: EdgeIx<Ix>, inc: EdgeIx<Ix>) -> Self {
         Self { outgoing: out, incoming: inc }
     }
 
-    fn outgoing(&self) -> EdgeIx { self.outgoing }
+    fn outgoing(&self) -> EdgeIx<Ix> { self.outgoing }
 
-    fn incoming(&self) -> EdgeIx { self.incoming }
+    fn incoming(&self) -> EdgeIx<Ix> { self.incoming }
 
-    fn set_incoming(&mut self, inc: EdgeIx) {
+    fn set_incoming(&mut self, inc: EdgeIx<Ix>) {
         self.incoming = inc;
     }
-    fn set_outgoing(&mut self, outgoing: EdgeIx) {
+    fn set_outgoing(&mut self, outgoing: EdgeIx<Ix>) {
         self.outgoing = outgoing;
     }
-    fn set_both(&mut self, edge: EdgeIx) {
+    fn set_both(&mut self, edge: EdgeIx<Ix>) {
         self.incoming = edge;
         self.outgoing = edge;
     }
-    fn set(&mut self, dir: &Direction, edge: EdgeIx) {
-        match dir {
-            &Direction::Incoming => self.incoming = edge,
-            &Direction::Outgoing => self.outgoing = edge,
-        }
-    }
-    fn next(&self, dir: &Direction) -> NodeIx {
+    fn next(&self, dir: &Direction) -> EdgeIx<Ix> {
         match dir {
-            &Direction::Incoming => self.incoming,
-            &Direction::Outgoing => self.outgoing,
+            Direction::Incoming => self.incoming,
+            Direction::Outgoing => self.outgoing,
         }
     }
-    fn next_mut(&mut self, dir: &Direction) -> &mut NodeIx {
+    fn next_mut(&mut self, dir: &Direction) -> &mut EdgeIx<Ix> {
         match dir {
-            &Direction::Incoming => &mut self.incoming,
-            &Direction::Outgoing => &mut self.outgoing,
+            Direction::Incoming => &mut self.incoming,
+            Direction::Outgoing => &mut self.outgoing,
         }
     }
 }
-impl NodeLink {
+impl<Ix: IndexType> NodeLink<Ix> {
 
-    fn new(src: NodeIx, dest: NodeIx) -> Self {
+    fn new(src: NodeIx<Ix>, dest: NodeIx<Ix>) -> Self {
         Self { src, dest }
     }
 
-    fn src(&self) -> NodeIx { self.src }
+    fn src(&self) -> NodeIx<Ix> { self.src }
 
-    fn dest(&self) -> NodeIx { self.dest }
+    fn dest(&self) -> NodeIx<Ix> { self.dest }
 
-    fn set_src(&mut self, src: NodeIx) {
-        self.src = src;
-    }
-    fn set_dest(&mut self, dest: NodeIx) {
-        self.dest = dest;
-    }
-    fn set_both(&mut self, node: NodeIx) {
-        self.src = node;
-        self.dest = node;
-    }
-    fn set(&mut self, dir: &Direction, node: NodeIx) {
+    fn next(&self, dir: &Direction) -> NodeIx<Ix> {
         match dir {
-            &Direction::Incoming => self.src = node,
-            &Direction::Outgoing => self.dest = node,
+            Direction::Incoming => self.dest,
+            Direction::Outgoing => self.src,
         }
     }
-
-    fn next(&self, dir: &Direction) -> NodeIx {
-        match dir {
-            &Direction::Incoming => self.dest,
-            &Direction::Outgoing => self.src,
-        }
-    }
-    fn next_mut(&mut self, dir: &Direction) -> &mut NodeIx {
+    fn next_mut(&mut self, dir: &Direction) -> &mut NodeIx<Ix> {
         match dir {
-            &Direction::Incoming => &mut self.dest,
-            &Direction::Outgoing => &mut self.src,
+            Direction::Incoming => &mut self.dest,
+            Direction::Outgoing => &mut self.src,
         }
     }
 }
 
-impl<N> Node<N> 
+impl<N, Ix> Node<N, Ix> 
 where
-    N: Clone 
+    N: Clone,
+    Ix: IndexType
 {
     #[inline]
     pub fn init(weight: N) -> Self {
         Self {  weight, edges: EdgeLink::default() }
     }
     #[inline]
-    pub fn new(weight: N, outgoing: EdgeIx, incoming: EdgeIx) -> Self {
+    pub fn new(weight: N, outgoing: EdgeIx<Ix>, incoming: EdgeIx<Ix>) -> Self {
         Self { weight, edges:  EdgeLink::new(outgoing, incoming) }
     }
 }
-impl<N> ops::Deref for Node<N>
+impl<N, Ix> ops::Deref for Node<N, Ix>
 where
     N: Clone 
 {
@@ -149,12 +140,13 @@ where
     }
 }
 
-impl<E> Edge<E>
+impl<E, Ix> Edge<E, Ix>
 where
-    E: Clone 
+    E: Clone,
+    Ix: IndexType
 {
     #[inline]
-    pub fn init(weight: E, a: NodeIx, b: NodeIx) -> Self {
+    pub fn init(weight: E, a: NodeIx<Ix>, b: NodeIx<Ix>) -> Self {
         Self { 
             weight,
             node: NodeLink::new(a, b),
@@ -163,10 +155,10 @@ where
     }
     #[inline]
     pub fn new(weight: E, 
-               a: NodeIx, 
-               b: NodeIx, 
-               next_outgoing: EdgeIx,
-               next_incoming: EdgeIx) -> Self  { 
+               a: NodeIx<Ix>, 
+               b: NodeIx<Ix>, 
+               next_outgoing: EdgeIx<Ix>,
+               next_incoming: EdgeIx<Ix>) -> Self  { 
         Self { 
             weight,
             node: NodeLink::new(a, b),
@@ -174,10 +166,10 @@ where
         }
     }
     #[inline]
-    pub fn src(self) -> NodeIx { self.node.src }
+    pub fn src(&self) -> NodeIx<Ix> { self.node.src }
 
     #[inline]
-    pub fn dest(self) -> NodeIx { self.node.dest }
+    pub fn dest(&self) -> NodeIx<Ix> { self.node.dest }
 
     pub fn is_loop(&self) -> bool {
         let src = self.node.src;
@@ -186,54 +178,56 @@ where
     }
 
     #[inline]
-    pub fn node(&self, dir: &Direction) -> NodeIx {
+    pub fn node(&self, dir: &Direction) -> NodeIx<Ix> {
         match dir {
             Direction::Outgoing => self.node.src(),
             Direction::Incoming => self.node.dest(),
         }
     }
 }
-pub trait Linked {
-    fn next_in(&self) -> EdgeIx;
+pub trait Linked<Ix: IndexType = DefaultIx> {
+    fn next_in(&self) -> EdgeIx<Ix>;
 
-    fn next_in_mut(&mut self) -> &mut EdgeIx;
+    fn next_in_mut(&mut self) -> &mut EdgeIx<Ix>;
 
-    fn next_out(&self) -> EdgeIx;
+    fn next_out(&self) -> EdgeIx<Ix>;
 
-    fn next_out_mut(&mut self) -> &mut EdgeIx;
+    fn next_out_mut(&mut self) -> &mut EdgeIx<Ix>;
 
-    fn next(&self, dir: &Direction) -> EdgeIx {
+    fn next(&self, dir: &Direction) -> EdgeIx<Ix> {
         match dir {
             Direction::Incoming => self.next_in(),
             Direction::Outgoing => self.next_out(),
         }
     }
-    fn next_mut(&mut self, dir: &Direction) -> &mut EdgeIx {
+    fn next_mut(&mut self, dir: &Direction) -> &mut EdgeIx<Ix> {
         match dir {
             Direction::Incoming => self.next_in_mut(),
             Direction::Outgoing => self.next_out_mut()
         }
     }
 }
-impl<E> Linked for Edge<E> 
+impl<E, Ix> Linked<Ix> for Edge<E, Ix> 
 where
-    E: Clone 
+    E: Clone,
+    Ix: IndexType
 {
-    fn next_out(&self) -> EdgeIx { self.next.outgoing }
-    fn next_in(&self) -> EdgeIx { self.next.incoming }
-    fn next_out_mut(&mut self) -> &mut EdgeIx { &mut self.next.outgoing }
-    fn next_in_mut(&mut self) -> &mut EdgeIx { &mut self.next.incoming }
+    fn next_out(&self) -> EdgeIx<Ix> { self.next.outgoing }
+    fn next_in(&self) -> EdgeIx<Ix> { self.next.incoming }
+    fn next_out_mut(&mut self) -> &mut EdgeIx<Ix> { &mut self.next.outgoing }
+    fn next_in_mut(&mut self) -> &mut EdgeIx<Ix> { &mut self.next.incoming }
 }
-impl<N> Linked for Node<N> 
+impl<N, Ix> Linked<Ix> for Node<N, Ix> 
 where
-    N: Clone
+    N: Clone,
+    Ix: IndexType
 {
-    fn next_out(&self) -> EdgeIx { self.edges.outgoing }
-    fn next_in(&self) -> EdgeIx { self.edges.incoming }
-    fn next_out_mut(&mut self) -> &mut EdgeIx { &mut self.edges.outgoing }
-    fn next_in_mut(&mut self) -> &mut EdgeIx { &mut self.edges.incoming }
+    fn next_out(&self) -> EdgeIx<Ix> { self.edges.outgoing }
+    fn next_in(&self) -> EdgeIx<Ix> { self.edges.incoming }
+    fn next_out_mut(&mut self) -> &mut EdgeIx<Ix> { &mut self.edges.outgoing }
+    fn next_in_mut(&mut self) -> &mut EdgeIx<Ix> { &mut self.edges.incoming }
 }
-impl<E> ops::Deref for Edge<E> 
+impl<E, Ix> ops::Deref for Edge<E, Ix> 
 where
     E: Clone
 {
@@ -243,14 +237,14 @@ where
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction { 
     Outgoing = 0 ,
     Incoming = 1, 
 }
-impl Into<usize> for Direction {
-    fn into(self) -> usize {
-        match self {
+impl From<Direction> for usize {
+    fn from(dir: Direction) -> usize {
+        match dir {
             Direction::Outgoing => 0,
             Direction::Incoming => 1,
         }
@@ -275,21 +269,79 @@ impl Direction {
 
     pub fn other(&self) -> Direction {
         match self {
-            &Direction::Incoming => Direction::Outgoing,
-            &Direction::Outgoing => Direction::Incoming,
+            Direction::Incoming => Direction::Outgoing,
+            Direction::Outgoing => Direction::Incoming,
+        }
+    }
+}
+
+/// Folds a parallel edge's weight into the existing edge's, see
+/// `ParallelEdges::Merge`
+pub type MergeFn<E> = Arc<dyn Fn(&mut E, E) + Send + Sync>;
+
+/// What `Graph::add_edge` does with an edge parallel to one already
+/// in the graph: one with the same endpoints, in either order if
+/// undirected
+pub enum ParallelEdges<E> {
+    /// Add it alongside; the default
+    Allow,
+    /// Refuse it with `RecolError::ParallelEdge`
+    Forbid,
+    /// Fold its weight into the existing edge's (the one `get_edge`
+    /// finds, if there are several), whose index is returned instead
+    Merge(MergeFn<E>),
+}
+impl<E> ParallelEdges<E> {
+
+    /// `Merge` with `f`, which may capture its environment
+    pub fn merge<F>(f: F) -> Self
+    where
+        F: Fn(&mut E, E) + Send + Sync + 'static,
+    {
+        Self::Merge(Arc::new(f))
+    }
+}
+impl<E> Clone for ParallelEdges<E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Allow => Self::Allow,
+            Self::Forbid => Self::Forbid,
+            Self::Merge(f) => Self::Merge(Arc::clone(f)),
         }
     }
 }
-pub struct Graph<N, E>
+impl<E> fmt::Debug for ParallelEdges<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allow => f.write_str("Allow"),
+            Self::Forbid => f.write_str("Forbid"),
+            Self::Merge(_) => f.write_str("Merge"),
+        }
+    }
+}
+
+/// Source of each `Graph`'s `id`
+static NEXT_GRAPH_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct Graph<N, E, Ix = DefaultIx>
 where
     N: Clone,
     E: Clone, 
 {
-    edges: Vec<Edge<E>>,
-    nodes: Vec<Node<N>>,
+    edges: Vec<Edge<E, Ix>>,
+    nodes: Vec<Node<N, Ix>>,
     directed: bool,
+    parallel_edges: ParallelEdges<E>,
+    self_loops: bool,
+    /// Unique to this graph, so maps made for another graph, which
+    /// may share its generations, are never taken for its own
+    id: u64,
+    /// Bumped whenever node indices change meaning, see `NodeMap`
+    node_generation: u64,
+    /// Bumped whenever edge indices change meaning, see `EdgeMap`
+    edge_generation: u64,
 }
-impl<N, E> Default for Graph<N, E> 
+impl<N, E, Ix> Default for Graph<N, E, Ix> 
 where
     N: Clone,
     E: Clone
@@ -298,15 +350,21 @@ where
         Self {
             directed: true,
             edges: Vec::new(),
-            nodes: Vec::new()
+            nodes: Vec::new(),
+            parallel_edges: ParallelEdges::Allow,
+            self_loops: true,
+            id: NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed),
+            node_generation: 0,
+            edge_generation: 0,
         }
     }
 }
 
-impl<N, E> Graph<N, E>
+impl<N, E, Ix> Graph<N, E, Ix>
 where
-    N: Clone + fmt::Debug,
-    E: Clone + fmt::Debug 
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
 {
 
     pub fn new_directed() -> Self {
@@ -316,92 +374,142 @@ where
     pub fn new_undirected() -> Self {
         Self { directed: false, ..Default::default() }
     }
-    pub fn add(&mut self, weight: N) -> NodeIx {
-        let node = Node::init(weight);
-        let node_ix = self.nodes.len() as NodeIx;
-        self.nodes.push(node);
-        node_ix
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn parallel_edges(&self) -> &ParallelEdges<E> { &self.parallel_edges }
+
+    /// Set how edges added from now on are treated when parallel to
+    /// an existing edge. Edges already in the graph are untouched;
+    /// `simple` collapses those.
+    pub fn set_parallel_edges(&mut self, policy: ParallelEdges<E>) {
+        self.parallel_edges = policy;
+    }
+
+    pub fn allows_self_loops(&self) -> bool { self.self_loops }
+
+    /// Set whether edges added from now on may join a node to itself.
+    /// If not, they are refused with `RecolError::SelfLoop`.
+    pub fn set_self_loops(&mut self, allow: bool) {
+        self.self_loops = allow;
+    }
+
+    /// Counts the changes to node indices: removals and clears. Adding
+    /// nodes leaves existing indices alone, so doesn't count.
+    pub fn node_generation(&self) -> u64 { self.node_generation }
+
+    /// Counts the changes to edge indices, as `node_generation`
+    pub fn edge_generation(&self) -> u64 { self.edge_generation }
+
+    pub fn add(&mut self, weight: N) -> NodeIx<Ix> {
+        self.try_add(weight).expect("Graph::add")
+    }
+
+    /// Like `add`, but fails rather than panicking if the index type
+    /// can't address another node
+    pub fn try_add(&mut self, weight: N) -> RecolResult<NodeIx<Ix>> {
+        let node_ix = NodeIx::new(self.nodes.len());
+        if node_ix.is_end() {
+            return Err(RecolError::IndexOverflow);
+        }
+        self.nodes.push(Node::init(weight));
+        Ok(node_ix)
     }
     pub fn edge_count(&self) -> usize { self.edges.len() }
     pub fn node_count(&self) -> usize { self.nodes.len() }
 
-    pub fn add_edge(&mut self, a: NodeIx, b: NodeIx, weight: E) -> EdgeIx {
-        let edge_ix = self.edges.len();
-        let mut edge = Edge::init(weight, a, b);
-        match index_twice(&mut self.nodes, a, b) {
-            Pair::None => panic!("Graph::add_edge: OOB"),
-            Pair::One(an) => {
-                let anc = an.clone();
-                edge.next = anc.edges;
-                an.edges.set_both(edge_ix);
+    /// Add an edge from `a` to `b`, returning its index, or the index
+    /// of the edge it was merged into under `ParallelEdges::Merge`.
+    /// Panics where `try_add_edge` would fail, including when the
+    /// graph forbids parallel edges or self loops and this is one.
+    pub fn add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> EdgeIx<Ix> {
+        self.try_add_edge(a, b, weight).expect("Graph::add_edge")
+    }
+
+    /// Like `add_edge`, but fails rather than panicking if either
+    /// endpoint is missing, the index type can't address another edge,
+    /// or the graph's policy refuses the edge
+    pub fn try_add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
+        for n in [a, b] {
+            if n.index() >= self.nodes.len() {
+                return Err(RecolError::node_not_found(n));
             }
-            Pair::Both(an, bn) => {
-                edge.next.set_outgoing(an.edges.outgoing);
-                edge.next.set_incoming(bn.edges.incoming);
-                an.edges.set_outgoing(edge_ix);
-                bn.edges.set_incoming(edge_ix);
+        }
+        if a == b && !self.self_loops {
+            return Err(RecolError::SelfLoop(NodeIx::new(a.index())));
+        }
+        match (&self.parallel_edges, self.get_edge(a, b)) {
+            (ParallelEdges::Allow, _) | (_, None) => (),
+            (ParallelEdges::Forbid, Some(_)) => {
+                return Err(RecolError::ParallelEdge(NodeIx::new(a.index()), NodeIx::new(b.index())));
             }
+            (ParallelEdges::Merge(merge), Some(ix)) => {
+                merge(&mut self.edges[ix.index()].weight, weight);
+                return Ok(ix);
+            }
+        }
+        let edge_ix = EdgeIx::new(self.edges.len());
+        if edge_ix.is_end() {
+            return Err(RecolError::IndexOverflow);
         }
+        let mut edge = Edge::init(weight, a, b);
+        link_edge(&mut self.nodes, &mut edge, edge_ix);
         self.edges.push(edge);
-        return edge_ix;
+        Ok(edge_ix)
     }
 
-    pub fn weight(&self, a: NodeIx) -> Option<&N> {
-        self.nodes.get(a).map(|n| &n.weight)
+    pub fn weight(&self, a: NodeIx<Ix>) -> Option<&N> {
+        self.nodes.get(a.index()).map(|n| &n.weight)
     }
 
-    pub fn weight_mut(&mut self, a: NodeIx) -> Option<&mut N> {
-        self.nodes.get_mut(a).map(|n| &mut n.weight)
+    pub fn weight_mut(&mut self, a: NodeIx<Ix>) -> Option<&mut N> {
+        self.nodes.get_mut(a.index()).map(|n| &mut n.weight)
     }
 
-    pub fn edge_weight(&self, a: EdgeIx) -> Option<&E> {
-        self.edges.get(a).map(|e| &e.weight)
+    pub fn edge_weight(&self, a: EdgeIx<Ix>) -> Option<&E> {
+        self.edges.get(a.index()).map(|e| &e.weight)
     }
-    pub fn edge_weight_mut(&mut self, a: EdgeIx) -> Option<&mut E> {
-        self.edges.get_mut(a).map(|n| &mut n.weight)
+    pub fn edge_weight_mut(&mut self, a: EdgeIx<Ix>) -> Option<&mut E> {
+        self.edges.get_mut(a.index()).map(|n| &mut n.weight)
     }
-    pub fn edge_endpoints(&self, e: EdgeIx) -> Option<(NodeIx, NodeIx)> {
-        match self.edges.get(e) {
-            Some(e) => return Some((e.node.src(), e.node.dest())),
-            _ => (),
-        }
-        None
+    pub fn edge_endpoints(&self, e: EdgeIx<Ix>) -> Option<(NodeIx<Ix>, NodeIx<Ix>)> {
+        self.edges.get(e.index()).map(|e| (e.node.src(), e.node.dest()))
     }
 
-    pub fn remove(&mut self, a: NodeIx) -> Option<N> where N: fmt::Debug {
-        let n = self.nodes.get(a)?;
-        // pritln!("REMOVING NODE {:?}", &n );
+    pub fn remove(&mut self, a: NodeIx<Ix>) -> Option<N> {
+        self.nodes.get(a.index())?;
         // Remove all edges to/from this node
         for dir in Direction::iter() {
-            // println!("REMOVING {:?} NODES", &dir);
             loop {
-                let next = self.nodes[a].next(&dir);
-                // println!("CHEKING {}", &next);
-                if next == EdgeIx::max_value() { break;}
-                let ret = self.remove_edge(next);
-                // println!("REMOVING CONN TO NODE IX {}: {}", a, next);
-                let _ = ret;
+                let next = self.nodes[a.index()].next(&dir);
+                if next.is_end() { break; }
+                self.remove_edge(next);
             }
         }
-        let node = self.nodes.swap_remove(a);
-        let swap_edges = match self.nodes.get(a) {
+        let node = self.nodes.swap_remove(a.index());
+        self.node_generation += 1;
+        // The last node was moved into `a`, so repoint its edges
+        let swap_edges = match self.nodes.get(a.index()) {
             None => return Some(node.weight),
-            Some(e) => &e.edges,
+            Some(n) => &n.edges,
         };
-        let new_ix = a;
-        for dir in Direction::iter() { let mut edges = EdgesMut::new(&mut self.edges, swap_edges.next(&dir), dir);
+        for dir in Direction::iter() {
+            let mut edges = EdgesMut::new(&mut self.edges, swap_edges.next(&dir), dir);
             while let Some(curr) = edges.next_edge() {
-                *curr.next_mut(&dir) = new_ix;
+                *curr.node.next_mut(&dir) = a;
             }
         }
         Some(node.weight)
     }
 
-    pub fn remove_edge(&mut self, eix: EdgeIx) -> Option<E> {
-        let (_e, e_node, e_next) = match self.edges.get_mut(eix) {
+    /// Like `remove`, but fails if `a` doesn't exist
+    pub fn try_remove(&mut self, a: NodeIx<Ix>) -> RecolResult<N> {
+        self.remove(a).ok_or_else(|| RecolError::node_not_found(a))
+    }
+
+    pub fn remove_edge(&mut self, eix: EdgeIx<Ix>) -> Option<E> {
+        let (e_node, e_next) = match self.edges.get(eix.index()) {
             None => return None,
             Some(e) => (
-                e.clone(),
                 NodeLink::new(e.node.src(), e.node.dest()),
                 EdgeLink::new(e.next.outgoing(), e.next.incoming()),
             )
@@ -413,142 +521,105 @@ where
     /// For edge e with endpoints `edge_node`, replace
     /// links to it with links to `edge_next`
     fn _change_edge_links(&mut self, 
-        e_node: NodeLink,
-        e_edge: EdgeLink,
-        eix: EdgeIx) 
+        e_node: NodeLink<Ix>,
+        e_edge: EdgeLink<Ix>,
+        eix: EdgeIx<Ix>) 
     {
-        for d in Direction::iter() {
-            let node = match self.nodes.get_mut(e_node.next(&d)) {
-                Some(n) => n,
-                None => {
-                    debug_assert!(false, "Edge endpoint
-                        dir={:?} index={:?} not found",
-                    &d, e_node.next(&d));
-                    return;
-                }
-            };
-            if node.next(&d) == eix {
-                *node.next_mut(&d) = e_edge.next(&d);
-            } else {
-                let mut edges = EdgesMut::new(&mut self.edges, node.next(&d), d);
-                while let Some(curr) = edges.next_edge() {
-                    if curr.next(&d) == eix {
-                        *curr.next_mut(&d) = e_edge.next(&d);
-                        break;
-                    }
-                }
-
-            }
-
-        }
+        change_edge_links(&mut self.nodes, &mut self.edges, e_node, e_edge, eix)
     }
-    fn _rm_edge_change_indices(&mut self, eix: EdgeIx) -> Option<E>
+    fn _rm_edge_change_indices(&mut self, eix: EdgeIx<Ix>) -> Option<E>
     {
-        let edge = self.edges.swap_remove(eix);
-        let swap = match self.edges.get(eix) {
+        let edge = self.edges.swap_remove(eix.index());
+        self.edge_generation += 1;
+        let swap = match self.edges.get(eix.index()) {
             None => return Some(edge.weight),
-            Some(ed) => ed.clone().node,
+            Some(ed) => ed.node,
         };
-        let swapped_e = EdgeIx::from(self.edges.len());
+        let swapped_e = EdgeIx::new(self.edges.len());
         let e_edge = EdgeLink::new(eix, eix);
         self._change_edge_links(swap, e_edge, swapped_e);
         Some(edge.weight)
     }
 
-    pub fn update_edge(&mut self, a: NodeIx, b: NodeIx, weight: E) -> EdgeIx {
+    /// Like `remove_edge`, but fails if `eix` doesn't exist
+    pub fn try_remove_edge(&mut self, eix: EdgeIx<Ix>) -> RecolResult<E> {
+        self.remove_edge(eix).ok_or_else(|| RecolError::edge_not_found(eix))
+    }
+
+    pub fn update_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> EdgeIx<Ix> {
+        self.try_update_edge(a, b, weight).expect("Graph::update_edge")
+    }
+
+    /// Like `update_edge`, but fails rather than panicking if a new
+    /// edge is needed and can't be added
+    pub fn try_update_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
         if let Some(ix) = self.get_edge(a, b) {
-            if let Some(ed) = self.edge_weight_mut(ix) {
-                *ed = weight;
-                return ix;
-            }
+            self.edges[ix.index()].weight = weight;
+            return Ok(ix);
         }
-        self.add_edge(a, b, weight)
-        
+        self.try_add_edge(a, b, weight)
     }
-    pub fn get_edge(&self, a: NodeIx, b: NodeIx) -> Option<EdgeIx> where N: fmt::Debug {
-        if let Some(n) = self.nodes.get(a) {
-            // println!("FOUND NODE {:?} LOOKING FOR {:?}", n, b);
-            if !self.directed {
-                if let Some((ix, _dir)) = self._edge_from_node_undir(n, b) {
-                    return Some(ix);
-                }
-            } else {
-                if let Some(ix) = self._edge_from_node_dir(n, b) {
-                    return Some(ix);
-                } 
-            }
-        }
-        return None;
+    pub fn get_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> Option<EdgeIx<Ix>> {
+        let n = self.nodes.get(a.index())?;
+        find_edge(&self.edges, &n.edges, b, self.directed).map(|(ix, _dir)| ix)
+    }
+    
+    /// The edges at `a` in direction `dir`: leaving `a` if `Outgoing`,
+    /// entering it if `Incoming`. In an undirected graph this is every
+    /// edge at `a`, oriented so `a` is the source (or target, if
+    /// `Incoming`). Parallel edges are each yielded.
+    pub fn edges_directed(&self, a: NodeIx<Ix>, dir: Direction) -> Edges<'_, E, Ix> {
+        let next = match self.nodes.get(a.index()) {
+            None => EdgeLink::default(),
+            Some(n) => n.edges.clone(),
+        };
+        Edges { edges: &self.edges, next, dir, directed: self.directed }
     }
 
-    fn _edge_from_node_undir(&self, 
-            n: &Node<N>, 
-            b: NodeIx
-        ) -> Option<(EdgeIx, Direction)> 
-    {
-        let (mut next_in, mut next_out) = (n.next_in(), n.next_out());
-        let edges = &mut self.edges.clone();
-        while let Some(e) = edges.get_mut(next_in) {
-            if e.next.outgoing() == b {
-                return Some((next_in, Direction::Outgoing))
-            }
-            next_in = e.next_in();
-        }
-        while let Some(e) = edges.get_mut(next_out) {
-            if e.next_in() == b {
-                return Some((next_out, Direction::Incoming))
-            }
-            next_out = e.next.outgoing();
-        }
-        return None;
+    /// Every edge from `a` to `b`, or between them if undirected
+    pub fn edges_connecting(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> EdgesConnecting<'_, E, Ix> {
+        EdgesConnecting { target: b, edges: self.edges_directed(a, Direction::Outgoing) }
     }
 
-    fn _edge_from_node_dir(&self, 
-            n: &Node<N>, 
-            b: NodeIx
-        ) -> Option<EdgeIx>
-    {
-        let mut eix = n.next(&Direction::Outgoing);
-        while let Some(edge) = self.edges.get(eix) {
-            if edge.node(&Direction::Incoming) == b {
-                return Some(eix);
-            }
-            eix = edge.next(&Direction::Outgoing);
-        }
-        None
+    /// Every edge in the graph, in index order
+    pub fn edge_references(&self) -> EdgeReferences<'_, E, Ix> {
+        EdgeReferences { iter: self.edges.iter().enumerate() }
     }
-    
-    pub fn neighbors(&self, a: NodeIx) -> Neighbors<E> {
+
+    pub fn neighbors(&self, a: NodeIx<Ix>) -> Neighbors<'_, E, Ix> {
         self.neighbors_directed(a, Direction::Outgoing)
     }
 
-    pub fn neighbors_directed(&self, a: NodeIx, dir: Direction) -> Neighbors<E> {
+    pub fn neighbors_directed(&self, a: NodeIx<Ix>, dir: Direction) -> Neighbors<'_, E, Ix> {
         let mut iter = self.neighbors_undirected(a);
         if self.directed {
             match dir {
-                Direction::Outgoing => iter.next.set_incoming(EdgeIx::max_value()),
-                Direction::Incoming => iter.next.set_outgoing(EdgeIx::max_value())
+                Direction::Outgoing => iter.next.set_incoming(EdgeIx::end()),
+                Direction::Incoming => iter.next.set_outgoing(EdgeIx::end())
             }
-            iter.src = NodeIx::max_value();
+            iter.src = NodeIx::end();
         }
-        return iter;
+        iter
     }
 
-    pub fn neighbors_undirected(&self, a: NodeIx) -> Neighbors<E> {
-        match self.nodes.get(a) {
+    pub fn neighbors_undirected(&self, a: NodeIx<Ix>) -> Neighbors<'_, E, Ix> {
+        match self.nodes.get(a.index()) {
             None => Neighbors::init(a, &self.edges),
             Some(n) => Neighbors::new(
-                a, &self.edges, n.clone().edges)
+                a, &self.edges, n.edges.clone())
         }
     }
 
     pub fn clear(&mut self) {
         self.nodes.clear();
         self.edges.clear();
+        self.node_generation += 1;
+        self.edge_generation += 1;
     }
 
     pub fn clear_edges(&mut self) {
         self.edges.clear();
+        self.edge_generation += 1;
         for node in &mut self.nodes {
             node.edges = EdgeLink::default();
         }
@@ -558,56 +629,82 @@ where
         (self.nodes.capacity(), self.edges.capacity())
     }
 
-    pub fn edges_log(&self) -> () where E: fmt::Debug {
+    pub fn edges_log(&self) where E: fmt::Debug {
         let mut out = String::from("");
         for e in &self.edges {
-        let (outg, inco) = match (&e.next.outgoing(), &e.next.incoming()) {
-            (&EdgeIx::MAX, &EdgeIx::MAX) => ("NONE".to_string(), "NONE".to_string()),
-            (&EdgeIx::MAX, inc) => ("NONE".to_string(), inc.to_string()),
-            (out, &EdgeIx::MAX) => (out.to_string(), "NONE".to_string()),
-            (out, inc) => (out.to_string(), inc.to_string()),
-        };
+        let (outg, inco) = (link_str(e.next.outgoing()), link_str(e.next.incoming()));
             out.push_str(&format!("Edge ( Data: {:?}, Nodes: [{} -> {}], Next: [O: {}, I: {}] ) \n",
                     &e.weight, &e.node.src(), &e.node.dest(), outg, inco))
         }
         println!("{}", out);
     }
 
-    pub fn nodes_log(&self) -> () where N: fmt::Debug {
+    pub fn nodes_log(&self) where N: fmt::Debug {
         let mut out = String::from("");
         for n in &self.nodes {
-            let (outg, inco) = match (&n.edges.outgoing(), &n.edges.incoming()) {
-                (&EdgeIx::MAX, &EdgeIx::MAX) => ("NONE".to_string(), "NONE".to_string()),
-                (&EdgeIx::MAX, inc) => ("NONE".to_string(), inc.to_string()),
-                (out, &EdgeIx::MAX) => (out.to_string(), "NONE".to_string()),
-                (out, inc) => (out.to_string(), inc.to_string()),
-            };
+            let (outg, inco) = (link_str(n.edges.outgoing()), link_str(n.edges.incoming()));
             out.push_str(&format!("Node ( Data: {:?}, Next: [O: {}, I: {}] ) \n",
                     &n.weight, outg, inco))
         }
         println!("{}", out);
     }
-    pub fn contains_edge(&self, a: NodeIx, b: NodeIx) -> bool {
+    pub fn contains_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> bool {
         self.get_edge(a, b).is_some()
     }
 
-    pub fn first_edge(&self, a: NodeIx, dir: Direction) -> Option<EdgeIx> {
-        match self.nodes.get(a) {
+    /// The number of edges from `a` to `b`, or between them if
+    /// undirected
+    pub fn edge_multiplicity(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> usize {
+        self.edges_connecting(a, b).count()
+    }
+
+    /// A simple graph: the same nodes, with each set of parallel edges
+    /// collapsed into the first of them and self loops dropped. The
+    /// collapsed edge's weight is the first's, with `reduce` folding
+    /// in the others in index order. The result forbids parallel
+    /// edges and self loops.
+    pub fn simple<F>(&self, mut reduce: F) -> Graph<N, E, Ix>
+    where
+        F: FnMut(&mut E, &E),
+    {
+        let mut g = self.filter_map(|_, w| Some(w.clone()), |_, _| None);
+        g.parallel_edges = ParallelEdges::Forbid;
+        g.self_loops = false;
+        let mut first: HashMap<_, usize> = HashMap::new();
+        for e in &self.edges {
+            let (a, b) = (e.src(), e.dest());
+            if a == b {
+                continue;
+            }
+            let pair = if self.directed || a < b { (a, b) } else { (b, a) };
+            match first.get(&pair) {
+                Some(&ix) => reduce(&mut g.edges[ix].weight, &e.weight),
+                None => {
+                    first.insert(pair, g.edges.len());
+                    g.push_edge(a, b, e.weight.clone());
+                }
+            }
+        }
+        g
+    }
+
+    pub fn first_edge(&self, a: NodeIx<Ix>, dir: Direction) -> Option<EdgeIx<Ix>> {
+        match self.nodes.get(a.index()) {
             None => None,
             Some(n) => {
                 let eix = n.next(&dir);
-                if eix == EdgeIx::max_value() {
+                if eix.is_end() {
                     None
                 } else { Some(eix) }
             }
         }
     }
-    pub fn next_edge(&self, e: EdgeIx, dir: Direction) -> Option<EdgeIx> {
-        match self.edges.get(e) {
+    pub fn next_edge(&self, e: EdgeIx<Ix>, dir: Direction) -> Option<EdgeIx<Ix>> {
+        match self.edges.get(e.index()) {
             None => None,
             Some(n) => {
                 let eix = n.next(&dir);
-                if eix == EdgeIx::max_value() {
+                if eix.is_end() {
                     None
                 } else { Some(eix) }
             }
@@ -627,36 +724,222 @@ where
         self.nodes.shrink_to_fit();
     }
 
-    pub fn nodes(&self) -> &[Node<N>] {
+    pub fn nodes(&self) -> &[Node<N, Ix>] {
         &self.nodes
     }
-    pub fn edges(&self) -> &[Edge<E>] {
+    pub fn edges(&self) -> &[Edge<E, Ix>] {
         &self.edges
     }
 
+    pub fn node_indices(&self) -> impl Iterator<Item = NodeIx<Ix>> {
+        (0..self.node_count()).map(NodeIx::new)
+    }
+
+    pub fn edge_indices(&self) -> impl Iterator<Item = EdgeIx<Ix>> {
+        (0..self.edge_count()).map(EdgeIx::new)
+    }
+
+    /// Keep only the nodes for which `keep` returns true, and the
+    /// edges between them. Unlike `remove`, survivors keep their
+    /// relative order, as do their edges, so indices only shift down
+    /// past removed entries.
+    pub fn retain_nodes<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(NodeIx<Ix>, &N) -> bool,
+    {
+        let nodes = mem::take(&mut self.nodes);
+        let (node_count, edge_count) = (nodes.len(), self.edges.len());
+        let mut moved = vec![NodeIx::end(); nodes.len()];
+        for (i, node) in nodes.into_iter().enumerate() {
+            if keep(NodeIx::new(i), &node.weight) {
+                moved[i] = NodeIx::new(self.nodes.len());
+                self.nodes.push(Node::init(node.weight));
+            }
+        }
+        let edges = mem::take(&mut self.edges);
+        for e in edges {
+            let (a, b) = (moved[e.src().index()], moved[e.dest().index()]);
+            if !a.is_end() && !b.is_end() {
+                self.push_edge(a, b, e.weight);
+            }
+        }
+        // indices only move up to fill gaps, so if nothing was
+        // dropped they are unchanged
+        if self.nodes.len() != node_count {
+            self.node_generation += 1;
+        }
+        if self.edges.len() != edge_count {
+            self.edge_generation += 1;
+        }
+    }
+
+    /// Keep only the edges for which `keep` returns true, in their
+    /// relative order
+    pub fn retain_edges<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(EdgeIx<Ix>, &E) -> bool,
+    {
+        let edges = mem::take(&mut self.edges);
+        let edge_count = edges.len();
+        for node in &mut self.nodes {
+            node.edges = EdgeLink::default();
+        }
+        for (i, e) in edges.into_iter().enumerate() {
+            if keep(EdgeIx::new(i), &e.weight) {
+                self.push_edge(e.src(), e.dest(), e.weight);
+            }
+        }
+        if self.edges.len() != edge_count {
+            self.edge_generation += 1;
+        }
+    }
+
+    /// A graph of the same shape, with every node and edge weight
+    /// mapped. Indices are unchanged. The result keeps the self loop
+    /// setting of `self`, but allows parallel edges.
+    pub fn map<'a, F, G, N2, E2>(&'a self, mut node_map: F, mut edge_map: G) -> Graph<N2, E2, Ix>
+    where
+        F: FnMut(NodeIx<Ix>, &'a N) -> N2,
+        G: FnMut(EdgeIx<Ix>, &'a E) -> E2,
+        N2: Clone,
+        E2: Clone,
+    {
+        let nodes = self.nodes.iter().enumerate()
+            .map(|(i, n)| Node { weight: node_map(NodeIx::new(i), &n.weight), edges: n.edges.clone() })
+            .collect();
+        let edges = self.edges.iter().enumerate()
+            .map(|(i, e)| Edge { weight: edge_map(EdgeIx::new(i), &e.weight), node: e.node, next: e.next.clone() })
+            .collect();
+        Graph { nodes, edges, directed: self.directed, self_loops: self.self_loops, ..Default::default() }
+    }
+
+    /// Like `map`, but nodes and edges mapped to `None` are left out,
+    /// as are edges whose endpoints were. Survivors keep their
+    /// relative order, as in `retain_nodes`.
+    pub fn filter_map<'a, F, G, N2, E2>(&'a self, mut node_map: F, mut edge_map: G) -> Graph<N2, E2, Ix>
+    where
+        F: FnMut(NodeIx<Ix>, &'a N) -> Option<N2>,
+        G: FnMut(EdgeIx<Ix>, &'a E) -> Option<E2>,
+        N2: Clone,
+        E2: Clone,
+    {
+        let mut g = Graph { directed: self.directed, self_loops: self.self_loops, ..Default::default() };
+        let mut moved = vec![NodeIx::end(); self.node_count()];
+        for (i, n) in self.nodes.iter().enumerate() {
+            if let Some(weight) = node_map(NodeIx::new(i), &n.weight) {
+                moved[i] = NodeIx::new(g.nodes.len());
+                g.nodes.push(Node::init(weight));
+            }
+        }
+        for (i, e) in self.edges.iter().enumerate() {
+            let (a, b) = (moved[e.src().index()], moved[e.dest().index()]);
+            if a.is_end() || b.is_end() {
+                continue;
+            }
+            if let Some(weight) = edge_map(EdgeIx::new(i), &e.weight) {
+                g.push_edge(a, b, weight);
+            }
+        }
+        g
+    }
+
+    /// Flip the direction of every edge, in place. Indices are
+    /// unchanged.
+    pub fn reverse(&mut self) {
+        for node in &mut self.nodes {
+            let edges = &mut node.edges;
+            mem::swap(&mut edges.outgoing, &mut edges.incoming);
+        }
+        for edge in &mut self.edges {
+            mem::swap(&mut edge.node.src, &mut edge.node.dest);
+            mem::swap(&mut edge.next.outgoing, &mut edge.next.incoming);
+        }
+    }
+
+    /// Add an edge known to fit: both endpoints exist and there are
+    /// no more edges than the graph once held
+    fn push_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) {
+        let eix = EdgeIx::new(self.edges.len());
+        let mut edge = Edge::init(weight, a, b);
+        link_edge(&mut self.nodes, &mut edge, eix);
+        self.edges.push(edge);
+    }
+
+}
+
+impl<N, E, Ix> Graph<N, E, Ix>
+where
+    N: Clone + Default,
+    E: Clone,
+    Ix: IndexType
+{
+    /// Add an edge for each `(a, b, weight)`, first adding as many
+    /// default weighted nodes as needed for `a` and `b` to exist.
+    /// Panics where `try_extend_with_edges` would fail.
+    pub fn extend_with_edges<I>(&mut self, edges: I)
+    where
+        I: IntoIterator<Item = (NodeIx<Ix>, NodeIx<Ix>, E)>,
+    {
+        self.try_extend_with_edges(edges).expect("Graph::extend_with_edges")
+    }
+
+    /// Like `extend_with_edges`, but fails rather than panicking on the
+    /// first edge `try_add_edge` refuses, such as one the graph's
+    /// policy forbids, or one naming the sentinel index. The edges
+    /// before it are kept, but no nodes are added for it.
+    pub fn try_extend_with_edges<I>(&mut self, edges: I) -> RecolResult<()>
+    where
+        I: IntoIterator<Item = (NodeIx<Ix>, NodeIx<Ix>, E)>,
+    {
+        let edges = edges.into_iter();
+        self.edges.reserve(edges.size_hint().0);
+        for (a, b, weight) in edges {
+            if a.is_end() || b.is_end() {
+                return Err(RecolError::IndexOverflow);
+            }
+            let needed = a.index().max(b.index()) + 1;
+            // refuse before growing the graph for an edge that can't
+            // be added; an edge to a new node is never a parallel one
+            if needed > self.nodes.len() {
+                if a == b && !self.self_loops {
+                    return Err(RecolError::SelfLoop(NodeIx::new(a.index())));
+                }
+                if EdgeIx::<Ix>::new(self.edges.len()).is_end() {
+                    return Err(RecolError::IndexOverflow);
+                }
+            }
+            while self.nodes.len() < needed {
+                self.try_add(N::default())?;
+            }
+            self.try_add_edge(a, b, weight)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
-pub struct Neighbors<'a, E: 'a> 
+pub struct Neighbors<'a, E: 'a, Ix: 'a = DefaultIx> 
 where
-    E: Clone 
+    E: Clone,
+    Ix: IndexType
 {
-    src: NodeIx,
-    edges: &'a [Edge<E>],
-    next: EdgeLink,
+    src: NodeIx<Ix>,
+    edges: &'a [Edge<E, Ix>],
+    next: EdgeLink<Ix>,
 }
-impl <'a, E> Neighbors<'a, E> 
+impl <'a, E, Ix> Neighbors<'a, E, Ix> 
 where
-    E: Clone
+    E: Clone,
+    Ix: IndexType
 {
-    pub fn detach(&self) -> WalkNeighbors {
+    pub fn detach(&self) -> WalkNeighbors<Ix> {
         WalkNeighbors {
             src: self.src,
-            next: EdgeLink::default()
+            next: self.next.clone()
         }
     }
 
-    pub fn init(src: NodeIx, edges: &'a [Edge<E>]) -> Self {
+    pub fn init(src: NodeIx<Ix>, edges: &'a [Edge<E, Ix>]) -> Self {
         Self { 
             src, edges,
             next: EdgeLink::default()
@@ -664,89 +947,175 @@ where
     }
 
     pub fn new(
-        src: NodeIx, 
-        edges: &'a [Edge<E>], 
-        next: EdgeLink) -> Self
+        src: NodeIx<Ix>, 
+        edges: &'a [Edge<E, Ix>], 
+        next: EdgeLink<Ix>) -> Self
     {
         Self { src, edges,  next }
 
     }
 
+    /// The next neighbor, along with the edge leading to it
+    pub fn next_edge(&mut self) -> Option<(EdgeIx<Ix>, NodeIx<Ix>)> {
+        // Outgoing edges first
+        let i = self.next.outgoing();
+        if let Some(ed) = self.edges.get(i.index()) {
+            self.next.set_outgoing(ed.next_out());
+            return Some((i, ed.node(&Direction::Incoming)));
+        }
+        // Then incoming edges, skipping self loops already
+        // yielded by the outgoing list
+        loop {
+            let i = self.next.incoming();
+            let ed = self.edges.get(i.index())?;
+            self.next.set_incoming(ed.next_in());
+            let n = ed.node(&Direction::Outgoing);
+            if n != self.src {
+                return Some((i, n));
+            }
+        }
+    }
+
 }
-struct EdgesMut<'a, E: 'a + Clone> {
-    edges: &'a mut [Edge<E>],
-    next: EdgeIx,
+struct EdgesMut<'a, E: 'a + Clone, Ix: 'a = DefaultIx> {
+    edges: &'a mut [Edge<E, Ix>],
+    next: EdgeIx<Ix>,
     dir: Direction
 }
-impl<'a, E> EdgesMut<'a, E> 
+impl<'a, E, Ix> EdgesMut<'a, E, Ix> 
 where
-    E: 'a + Clone
+    E: 'a + Clone,
+    Ix: IndexType
 {
-    fn next_edge(&mut self) -> Option<&mut Edge<E>> {
+    fn next_edge(&mut self) -> Option<&mut Edge<E, Ix>> {
         self.next().map(|t| t.1)
     } 
 
-    fn new(edges: &'a mut [Edge<E>], next: EdgeIx, dir: Direction) -> Self {
+    fn new(edges: &'a mut [Edge<E, Ix>], next: EdgeIx<Ix>, dir: Direction) -> Self {
         Self { edges, next, dir}
     }
 
-    fn next(&mut self) -> Option<(EdgeIx, &mut Edge<E>)> {
+    fn next(&mut self) -> Option<(EdgeIx<Ix>, &mut Edge<E, Ix>)> {
         let ix = self.next;
-        match self.edges.get_mut(self.next) {
+        match self.edges.get_mut(self.next.index()) {
             Some(edge) => { 
                 self.next = edge.next(&self.dir);
-                return Some((ix, edge));
+                Some((ix, edge))
             }
             None => None,
         } 
     }
 }
 
+/// The edges at a node, from `Graph::edges_directed`
 #[derive(Debug)]
-pub struct Edges<'a, E: 'a> where E: Clone {
-    src: NodeIx,
-    edges: &'a [Edge<E>],
-    next: EdgeLink,
+pub struct Edges<'a, E: 'a, Ix: 'a + IndexType = DefaultIx> where E: Clone {
+    edges: &'a [Edge<E, Ix>],
+    next: EdgeLink<Ix>,
     dir: Direction,
+    directed: bool,
 }
-impl <'a, E: 'a> Iterator for Edges<'a, E> where E: Clone {
-    type Item = EdgeRef<'a, E>;
-    fn next(&mut self) -> Option<EdgeRef<'a, E>> {
+impl <'a, E: 'a, Ix: IndexType> Iterator for Edges<'a, E, Ix> where E: Clone {
+    type Item = EdgeRef<'a, E, Ix>;
+    fn next(&mut self) -> Option<EdgeRef<'a, E, Ix>> {
         let i = self.next.next(&self.dir);
-        if let Some(Edge { node, weight, next}) = self.edges.get(i) {
+        if let Some(Edge { node, weight, next }) = self.edges.get(i.index()) {
             *self.next.next_mut(&self.dir) = next.next(&self.dir);
-            return Some(
-                    EdgeRef {  ix: i, node: node.clone(), weight }
-                );
+            return Some(EdgeRef { ix: i, node: *node, weight });
+        }
+        if self.directed {
+            return None;
         }
-        return None;
+        // An undirected edge may be in either list, so walk the other
+        // too, flipping its edges to keep `src` on the same side.
+        // Self loops are in both lists and were already seen.
+        let other = self.dir.other();
+        while let Some(Edge { node, weight, next }) = self.edges.get(self.next.next(&other).index()) {
+            let i = self.next.next(&other);
+            *self.next.next_mut(&other) = next.next(&other);
+            if node.src != node.dest {
+                let node = NodeLink::new(node.dest, node.src);
+                return Some(EdgeRef { ix: i, node, weight });
+            }
+        }
+        None
+    }
+}
+
+/// The edges joining two nodes, from `Graph::edges_connecting`
+#[derive(Debug)]
+pub struct EdgesConnecting<'a, E: 'a, Ix: 'a + IndexType = DefaultIx> where E: Clone {
+    target: NodeIx<Ix>,
+    edges: Edges<'a, E, Ix>,
+}
+impl <'a, E: 'a, Ix: IndexType> Iterator for EdgesConnecting<'a, E, Ix> where E: Clone {
+    type Item = EdgeRef<'a, E, Ix>;
+    fn next(&mut self) -> Option<EdgeRef<'a, E, Ix>> {
+        let target = self.target;
+        self.edges.by_ref().find(|e| e.target() == target)
+    }
+}
+
+/// Every edge of a graph, from `Graph::edge_references`
+#[derive(Debug)]
+pub struct EdgeReferences<'a, E: 'a, Ix: 'a + IndexType = DefaultIx> where E: Clone {
+    iter: iter::Enumerate<slice::Iter<'a, Edge<E, Ix>>>,
+}
+impl <'a, E: 'a, Ix: IndexType> Iterator for EdgeReferences<'a, E, Ix> where E: Clone {
+    type Item = EdgeRef<'a, E, Ix>;
+    fn next(&mut self) -> Option<EdgeRef<'a, E, Ix>> {
+        self.iter.next().map(|(i, e)| EdgeRef { ix: EdgeIx::new(i), node: e.node, weight: &e.weight })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
     }
 }
 #[derive(Debug)]
-pub struct WalkNeighbors {
-    src: NodeIx,
-    next: EdgeLink,
+pub struct WalkNeighbors<Ix: IndexType = DefaultIx> {
+    src: NodeIx<Ix>,
+    next: EdgeLink<Ix>,
 }
-impl Clone for WalkNeighbors {
+impl<Ix: IndexType> Clone for WalkNeighbors<Ix> {
     fn clone(&self) -> Self {
         WalkNeighbors { src: self.src, next: self.next.clone() }
     }
 }
+/// A borrowed edge, along with its index and endpoints
 #[derive(Debug)]
-pub struct EdgeRef<'a, E: 'a> {
-    ix: EdgeIx,
-    node: NodeLink,
+pub struct EdgeRef<'a, E: 'a, Ix: IndexType = DefaultIx> {
+    ix: EdgeIx<Ix>,
+    node: NodeLink<Ix>,
     weight: &'a E,
 }
+impl<'a, E, Ix: IndexType> Clone for EdgeRef<'a, E, Ix> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, E, Ix: IndexType> Copy for EdgeRef<'a, E, Ix> {}
+impl<'a, E, Ix: IndexType> EdgeRef<'a, E, Ix> {
+
+    #[inline]
+    pub fn id(&self) -> EdgeIx<Ix> { self.ix }
+
+    #[inline]
+    pub fn source(&self) -> NodeIx<Ix> { self.node.src }
+
+    #[inline]
+    pub fn target(&self) -> NodeIx<Ix> { self.node.dest }
+
+    #[inline]
+    pub fn weight(&self) -> &'a E { self.weight }
+}
 
-impl WalkNeighbors {
+impl<Ix: IndexType> WalkNeighbors<Ix> {
 
     pub fn next<N, E>(&mut self,
-        g: &Graph<N, E>) -> Option<(EdgeIx, NodeIx)>  
+        g: &Graph<N, E, Ix>) -> Option<(EdgeIx<Ix>, NodeIx<Ix>)>  
     where
         N: Clone, E: Clone
     {
-        match g.edges.get(self.next.next(&Direction::Outgoing)) {
+        match g.edges.get(self.next.next(&Direction::Outgoing).index()) {
             None => {  },
             Some(e) => {
                 let ed = self.next.next(&Direction::Outgoing);
@@ -754,25 +1123,25 @@ impl WalkNeighbors {
                 return Some((ed, e.node.next(&Direction::Incoming)));
             }
         }
-        while let Some(edge) = g.edges.get(self.next.incoming()) {
+        while let Some(edge) = g.edges.get(self.next.incoming().index()) {
             let ed = self.next.incoming();
-            self.next.set_incoming(edge.next.outgoing());
-            if edge.node(&Direction::Incoming) != self.src {
+            self.next.set_incoming(edge.next.incoming());
+            if edge.node(&Direction::Outgoing) != self.src {
                 return Some((ed, edge.node(&Direction::Outgoing)));
             }
         }
         None
     }
-    pub fn next_node<N, E>(&mut self, g: &Graph<N, E>)
-        -> Option<NodeIx> 
+    pub fn next_node<N, E>(&mut self, g: &Graph<N, E, Ix>)
+        -> Option<NodeIx<Ix>> 
     where
         N: Clone, E: Clone
     {
         self.next(g).map(|t| t.1)
     }
 
-    pub fn next_edge<N, E>(&mut self, g: &Graph<N, E>)
-        -> Option<NodeIx> 
+    pub fn next_edge<N, E>(&mut self, g: &Graph<N, E, Ix>)
+        -> Option<EdgeIx<Ix>> 
     where
         N: Clone, E: Clone
     {
@@ -785,34 +1154,104 @@ impl WalkNeighbors {
     } */
 }
 
-impl<'a, E> Iterator for Neighbors<'a, E> 
+impl<'a, E, Ix> Iterator for Neighbors<'a, E, Ix> 
 where
-    E: Clone 
+    E: Clone,
+    Ix: IndexType
 {
-    type Item = NodeIx;
+    type Item = NodeIx<Ix>;
 
-    fn next(&mut self) -> Option<NodeIx> {
-        for dir in Direction::iter() {
-            if dir == Direction::Incoming {
-                while let Some(ed) = self.edges.get(self.next.incoming()) {
-                    self.next.set_incoming(ed.next_in());
-                    let n = ed.node.next(&Direction::Outgoing);
-                    if n != self.src {
-                        return Some(n);
-                    }
-                }
+    fn next(&mut self) -> Option<NodeIx<Ix>> {
+        self.next_edge().map(|(_, n)| n)
+    }
+}
+
+/// Push edge `eix` onto the front of the outgoing list of its
+/// source and the incoming list of its target. Returns `false`
+/// if either endpoint is out of bounds.
+fn link_edge<N: Clone, E: Clone, Ix: IndexType>(
+    nodes: &mut [Node<N, Ix>],
+    edge: &mut Edge<E, Ix>,
+    eix: EdgeIx<Ix>) -> bool
+{
+    match index_twice(nodes, edge.node.src().index(), edge.node.dest().index()) {
+        Pair::None => return false,
+        Pair::One(an) => {
+            edge.next = an.edges.clone();
+            an.edges.set_both(eix);
+        }
+        Pair::Both(an, bn) => {
+            edge.next.set_outgoing(an.edges.outgoing);
+            edge.next.set_incoming(bn.edges.incoming);
+            an.edges.set_outgoing(eix);
+            bn.edges.set_incoming(eix);
+        }
+    }
+    true
+}
 
+/// For edge `eix` with endpoints `e_node`, replace links to it
+/// in the lists of both endpoints with links to `e_edge`
+fn change_edge_links<N: Clone, E: Clone, Ix: IndexType>(
+    nodes: &mut [Node<N, Ix>],
+    edges: &mut [Edge<E, Ix>],
+    e_node: NodeLink<Ix>,
+    e_edge: EdgeLink<Ix>,
+    eix: EdgeIx<Ix>)
+{
+    for d in Direction::iter() {
+        let node = match nodes.get_mut(e_node.next(&d).index()) {
+            Some(n) => n,
+            None => {
+                debug_assert!(false, "Edge endpoint
+                    dir={:?} index={:?} not found",
+                &d, e_node.next(&d));
+                return;
             }
-            match self.edges.get(self.next.next(&dir)) {
-                None => {},
-                Some(ed) => {
-                    self.next.set(&dir, ed.node.next(&dir));
-                    return Some(ed.node.next(&dir));
+        };
+        if node.next(&d) == eix {
+            *node.next_mut(&d) = e_edge.next(&d);
+        } else {
+            let mut edges = EdgesMut::new(edges, node.next(&d), d);
+            while let Some(curr) = edges.next_edge() {
+                if curr.next(&d) == eix {
+                    *curr.next_mut(&d) = e_edge.next(&d);
+                    break;
                 }
+            }
+        }
+    }
+}
 
+/// Find the first edge to `b` from the node whose lists start at
+/// `next`. Only the outgoing list is searched if `directed`.
+fn find_edge<E: Clone, Ix: IndexType>(
+    edges: &[Edge<E, Ix>],
+    next: &EdgeLink<Ix>,
+    b: NodeIx<Ix>,
+    directed: bool) -> Option<(EdgeIx<Ix>, Direction)>
+{
+    for dir in Direction::iter() {
+        if directed && dir == Direction::Incoming {
+            break;
+        }
+        let mut eix = next.next(&dir);
+        while let Some(edge) = edges.get(eix.index()) {
+            if edge.node(&dir.other()) == b {
+                return Some((eix, dir));
             }
+            eix = edge.next(&dir);
         }
-    None 
+    }
+    None
+}
+
+/// Render an edge list link for the `*_log` methods
+fn link_str<Ix: IndexType>(eix: EdgeIx<Ix>) -> String {
+    if eix.is_end() {
+        "NONE".to_string()
+    } else {
+        eix.to_string()
     }
 }
 
@@ -841,16 +1280,17 @@ fn index_twice<T>(slc: &mut [T], a: usize, b: usize) -> Pair<&mut T> {
     }
 }
 
-impl<N, E> fmt::Debug for Graph<N, E> 
+impl<N, E, Ix> fmt::Debug for Graph<N, E, Ix> 
 where
     N: fmt::Debug + Clone,
     E: fmt::Debug + Clone,
+    Ix: IndexType,
 
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut fmt_struct = f.debug_struct("Graph");
-        fmt_struct.field(&"# nodes", &self.node_count());
-        fmt_struct.field(&"# edges", &self.edge_count());
+        fmt_struct.field("# nodes", &self.node_count());
+        fmt_struct.field("# edges", &self.edge_count());
         if size_of::<N>() != 0 && self.edge_count() > 0 {
             fmt_struct.field("Nodes", &self.nodes );
         }
@@ -860,9 +1300,10 @@ where
         fmt_struct.finish()
     }
 }
-impl<N> fmt::Debug for Node<N> 
+impl<N, Ix> fmt::Debug for Node<N, Ix> 
 where
-    N: fmt::Debug + Clone 
+    N: fmt::Debug + Clone,
+    Ix: IndexType
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
@@ -872,9 +1313,10 @@ where
         fmt_struct.finish()
     }
 }
-impl<E> fmt::Debug for Edge<E> 
+impl<E, Ix> fmt::Debug for Edge<E, Ix> 
 where
-    E: fmt::Debug + Clone 
+    E: fmt::Debug + Clone,
+    Ix: IndexType
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
@@ -885,19 +1327,14 @@ where
         fmt_struct.finish()
     }
 }
-impl fmt::Debug for EdgeLink {
+impl<Ix: IndexType> fmt::Debug for EdgeLink<Ix> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (out, inc) = match (&self.outgoing, &self.incoming) {
-            (&EdgeIx::MAX, &EdgeIx::MAX) => ("NONE".to_string(), "NONE".to_string()),
-            (&EdgeIx::MAX, inc) => ("NONE".to_string(), inc.to_string()),
-            (out, &EdgeIx::MAX) => (out.to_string(), "NONE".to_string()),
-            (out, inc) => (out.to_string(), inc.to_string()),
-        };
+        let (out, inc) = (link_str(self.outgoing), link_str(self.incoming));
         f.write_fmt(format_args!("outgoing [{}] -->> incoming: [{}]",
                 &out, &inc))
     }
 }
-impl fmt::Debug for NodeLink {
+impl<Ix: IndexType> fmt::Debug for NodeLink<Ix> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!("src [{}] -->> dest: [{}]",
                 &self.src, &self.dest))
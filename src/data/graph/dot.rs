@@ -0,0 +1,434 @@
+//! Graphviz DOT output and input.
+//!
+//! `Dot` renders a graph through `Display`, so it can be printed or
+//! written to a file as is. `Graph::from_dot` reads back a subset of
+//! the language, enough for hand drawn fixtures: node and edge
+//! statements with attribute lists, and comments. Subgraphs are not
+//! supported.
+//!
+use std::{collections::{hash_map::Entry, HashMap}, fmt, iter::Peekable, str::Chars};
+use crate::error::{RecolError, RecolResult};
+use super::{EdgeIx, Graph, IndexType, NodeIx};
+
+type Label<'a, I, W> = Box<dyn Fn(I, &W) -> String + 'a>;
+
+/// A DOT formatter for a `Graph`. Nodes are named by their index and
+/// labelled by the label closures.
+///
+/// ```
+/// use recollection::data::graph::{dot::Dot, Graph};
+///
+/// let mut g = Graph::<&str, u32>::new_directed();
+/// let a = g.add("a");
+/// let b = g.add("b");
+/// g.add_edge(a, b, 7);
+/// let dot = Dot::new(&g).hide_edge_labels().to_string();
+/// assert_eq!(dot, "digraph {\n    0 [ label = \"a\" ]\n    1 [ label = \"b\" ]\n    0 -> 1\n}\n");
+/// ```
+pub struct Dot<'a, N: Clone, E: Clone, Ix = super::DefaultIx> {
+    graph: &'a Graph<N, E, Ix>,
+    node_label: Label<'a, NodeIx<Ix>, N>,
+    edge_label: Label<'a, EdgeIx<Ix>, E>,
+    node_labels: bool,
+    edge_labels: bool,
+}
+
+impl<'a, N, E, Ix> Dot<'a, N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+
+    /// Label nodes and edges by their weights
+    pub fn new(graph: &'a Graph<N, E, Ix>) -> Self
+    where
+        N: fmt::Display,
+        E: fmt::Display,
+    {
+        Self::with_labels(graph, |_, n| n.to_string(), |_, e| e.to_string())
+    }
+
+    /// Label nodes and edges by the given closures, which also receive
+    /// the index of what they label
+    pub fn with_labels<F, G>(graph: &'a Graph<N, E, Ix>, node_label: F, edge_label: G) -> Self
+    where
+        F: Fn(NodeIx<Ix>, &N) -> String + 'a,
+        G: Fn(EdgeIx<Ix>, &E) -> String + 'a,
+    {
+        Self {
+            graph,
+            node_label: Box::new(node_label),
+            edge_label: Box::new(edge_label),
+            node_labels: true,
+            edge_labels: true,
+        }
+    }
+
+    pub fn hide_node_labels(mut self) -> Self {
+        self.node_labels = false;
+        self
+    }
+
+    pub fn hide_edge_labels(mut self) -> Self {
+        self.edge_labels = false;
+        self
+    }
+}
+
+impl<'a, N, E, Ix> fmt::Display for Dot<'a, N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let g = self.graph;
+        let (kind, arrow) = if g.is_directed() { ("digraph", "->") } else { ("graph", "--") };
+        writeln!(f, "{} {{", kind)?;
+        for (i, node) in g.nodes().iter().enumerate() {
+            write!(f, "    {}", i)?;
+            if self.node_labels {
+                write!(f, " [ label = \"{}\" ]", Escaped(&(self.node_label)(NodeIx::new(i), &node.weight)))?;
+            }
+            writeln!(f)?;
+        }
+        for (i, edge) in g.edges().iter().enumerate() {
+            write!(f, "    {} {} {}", edge.src(), arrow, edge.dest())?;
+            if self.edge_labels {
+                write!(f, " [ label = \"{}\" ]", Escaped(&(self.edge_label)(EdgeIx::new(i), &edge.weight)))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// A string escaped for use inside a quoted DOT id
+struct Escaped<'a>(&'a str);
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<Ix: IndexType> Graph<String, String, Ix> {
+
+    /// Parse a DOT graph. Each node's weight is its `label` attribute,
+    /// or failing that its id, and each edge's weight is its `label`
+    /// or empty. Nodes are added in order of first mention. Fails if
+    /// the graph is malformed, or too big for `Ix`.
+    pub fn from_dot(src: &str) -> RecolResult<Self> {
+        let mut tokens = Lexer { chars: src.chars().peekable() };
+        let mut next = |what: &str| tokens.next().transpose()?
+            .ok_or_else(|| dot_error(format!("expected {}, found end of input", what)));
+
+        let mut token = next("graph")?;
+        if token == Token::Id("strict".into()) {
+            token = next("graph")?;
+        }
+        let mut graph = match token {
+            Token::Id(kw) if kw == "digraph" => Graph::new_directed(),
+            Token::Id(kw) if kw == "graph" => Graph::new_undirected(),
+            t => return Err(dot_error(format!("expected graph or digraph, found {:?}", t))),
+        };
+        token = next("{")?;
+        if let Token::Id(_) = token {
+            token = next("{")?;
+        }
+        if token != Token::LBrace {
+            return Err(dot_error(format!("expected {{, found {:?}", token)));
+        }
+
+        let mut ids = HashMap::new();
+        let mut node = |graph: &mut Self, id: String| -> RecolResult<NodeIx<Ix>> {
+            match ids.entry(id) {
+                Entry::Occupied(e) => Ok(*e.get()),
+                Entry::Vacant(e) => {
+                    let n = graph.try_add(e.key().clone())?;
+                    Ok(*e.insert(n))
+                }
+            }
+        };
+        // A token read past the end of the previous statement
+        let mut pending = None;
+        loop {
+            let first = match pending.take() {
+                Some(token) => token,
+                None => next("}")?,
+            };
+            let id = match first {
+                Token::RBrace => break,
+                Token::Semi => continue,
+                Token::Id(id) => id,
+                t => return Err(dot_error(format!("expected a statement, found {:?}", t))),
+            };
+            let mut token = next("; or }")?;
+            // Defaults for the graph, nodes or edges, and `id = id`
+            // attributes, which don't affect the structure
+            if matches!(id.as_str(), "graph" | "node" | "edge") && token == Token::LBracket {
+                attributes(&mut next)?;
+                continue;
+            }
+            if token == Token::Eq {
+                match next("an id")? {
+                    Token::Id(_) => continue,
+                    t => return Err(dot_error(format!("expected an id, found {:?}", t))),
+                }
+            }
+            let mut path = vec![node(&mut graph, id)?];
+            while let Token::Edge(directed) = token {
+                if directed != graph.is_directed() {
+                    let op = if directed { "->" } else { "--" };
+                    return Err(dot_error(format!("{} used in a {}", op, if directed { "graph" } else { "digraph" })));
+                }
+                match next("an id")? {
+                    Token::Id(id) => path.push(node(&mut graph, id)?),
+                    t => return Err(dot_error(format!("expected an id, found {:?}", t))),
+                }
+                token = next("; or }")?;
+            }
+            let mut label = None;
+            while token == Token::LBracket {
+                label = attributes(&mut next)?.or(label);
+                token = next("; or }")?;
+            }
+            if path.len() == 1 {
+                if let Some(label) = label {
+                    graph.nodes[path[0].index()].weight = label;
+                }
+            } else {
+                let label = label.unwrap_or_default();
+                for pair in path.windows(2) {
+                    graph.try_add_edge(pair[0], pair[1], label.clone())?;
+                }
+            }
+            match token {
+                Token::Semi => {}
+                Token::RBrace => break,
+                // The `;` between statements is optional
+                Token::Id(id) => pending = Some(Token::Id(id)),
+                t => return Err(dot_error(format!("expected ; or }}, found {:?}", t))),
+            }
+        }
+        match tokens.next().transpose()? {
+            None => Ok(graph),
+            Some(t) => Err(dot_error(format!("unexpected {:?} after the graph", t))),
+        }
+    }
+}
+
+/// Read an attribute list up to its closing `]`, returning the last
+/// `label` in it
+fn attributes<F>(next: &mut F) -> RecolResult<Option<String>>
+where
+    F: FnMut(&str) -> RecolResult<Token>,
+{
+    let mut label = None;
+    loop {
+        let key = match next("]")? {
+            Token::RBracket => return Ok(label),
+            Token::Semi | Token::Comma => continue,
+            Token::Id(key) => key,
+            t => return Err(dot_error(format!("expected an attribute, found {:?}", t))),
+        };
+        if next("=")? != Token::Eq {
+            return Err(dot_error(format!("expected = after {}", key)));
+        }
+        match next("a value")? {
+            Token::Id(value) if key == "label" => label = Some(value),
+            Token::Id(_) => {}
+            t => return Err(dot_error(format!("expected a value, found {:?}", t))),
+        }
+    }
+}
+
+fn dot_error(msg: String) -> RecolError {
+    RecolError::General(format!("dot: {}", msg))
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Id(String),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Eq,
+    Semi,
+    Comma,
+    /// `->` if directed, else `--`
+    Edge(bool),
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+
+    /// Skip whitespace and comments
+    fn skip(&mut self) -> RecolResult<()> {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.chars.next();
+            } else if c == '#' {
+                self.chars.by_ref().find(|&c| c == '\n');
+            } else if c == '/' {
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                match ahead.next() {
+                    Some('/') => { self.chars.by_ref().find(|&c| c == '\n'); }
+                    Some('*') => {
+                        self.chars.nth(1);
+                        let mut prev = ' ';
+                        loop {
+                            match self.chars.next() {
+                                Some('/') if prev == '*' => break,
+                                Some(c) => prev = c,
+                                None => return Err(dot_error("unterminated comment".into())),
+                            }
+                        }
+                    }
+                    _ => return Ok(()),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn quoted(&mut self) -> RecolResult<String> {
+        let mut id = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(id),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => id.push('\n'),
+                    // A backslash newline continues the line
+                    Some('\n') => {}
+                    Some(c @ ('"' | '\\')) => id.push(c),
+                    Some(c) => { id.push('\\'); id.push(c); }
+                    None => break,
+                },
+                Some(c) => id.push(c),
+                None => break,
+            }
+        }
+        Err(dot_error("unterminated string".into()))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = RecolResult<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.skip() {
+            return Some(Err(e));
+        }
+        let token = match self.chars.next()? {
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '=' => Token::Eq,
+            ';' => Token::Semi,
+            ',' => Token::Comma,
+            '"' => return Some(self.quoted().map(Token::Id)),
+            '-' if self.chars.peek() == Some(&'>') => { self.chars.next(); Token::Edge(true) }
+            '-' if self.chars.peek() == Some(&'-') => { self.chars.next(); Token::Edge(false) }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    id.push(c);
+                    self.chars.next();
+                }
+                Token::Id(id)
+            }
+            c => return Some(Err(dot_error(format!("unexpected character {:?}", c)))),
+        };
+        Some(Ok(token))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn undirected_with_labels() {
+        let mut g = Graph::<&str, f32>::new_undirected();
+        let a = g.add("say \"hi\"");
+        let b = g.add("b");
+        g.add_edge(a, b, 1.5);
+        let dot = Dot::with_labels(&g, |n, w| format!("{}: {}", n, w), |_, w| format!("{:.2}", w))
+            .to_string();
+        assert_eq!(dot, concat!(
+            "graph {\n",
+            "    0 [ label = \"0: say \\\"hi\\\"\" ]\n",
+            "    1 [ label = \"1: b\" ]\n",
+            "    0 -- 1 [ label = \"1.50\" ]\n",
+            "}\n"));
+        let dot = Dot::new(&g).hide_node_labels().hide_edge_labels().to_string();
+        assert_eq!(dot, "graph {\n    0\n    1\n    0 -- 1\n}\n");
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let mut g = Graph::<String, String>::new_directed();
+        let a = g.add("a \"quoted\"".into());
+        let b = g.add("b".into());
+        g.add_edge(a, b, "ab".into());
+        g.add_edge(b, b, "loop".into());
+        let h = Graph::<String, String>::from_dot(&Dot::new(&g).to_string()).unwrap();
+        assert!(h.is_directed());
+        assert_eq!(h.nodes().iter().map(|n| n.weight.as_str()).collect::<Vec<_>>(), ["a \"quoted\"", "b"]);
+        assert_eq!(h.edges().iter().map(|e| e.weight.as_str()).collect::<Vec<_>>(), ["ab", "loop"]);
+        assert_eq!(h.get_edge(b, b), Some(EdgeIx::new(1)));
+    }
+
+    #[test]
+    fn parse_fixture() {
+        let src = r#"
+            strict graph fixture {
+                // defaults are ignored
+                node [shape = box];
+                rankdir = LR;
+                a; b [label = "B"]
+                a -- b -- c [color = red, label = "chain"];
+                /* a comment */ c -- a
+            }
+        "#;
+        let g = Graph::<String, String>::from_dot(src).unwrap();
+        assert!(!g.is_directed());
+        assert_eq!(g.nodes().iter().map(|n| n.weight.as_str()).collect::<Vec<_>>(), ["a", "B", "c"]);
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.edges()[1].weight, "chain");
+        assert_eq!(g.edges()[2].weight, "");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Graph::<String, String>::from_dot("graph { a -> b }").is_err());
+        assert!(Graph::<String, String>::from_dot("digraph { a -> }").is_err());
+        assert!(Graph::<String, String>::from_dot("digraph { a [label = \"x ] }").is_err());
+        assert!(Graph::<String, String>::from_dot("tree { }").is_err());
+        // u8 indices reserve 255 as the end marker, so 256 nodes overflow
+        let ids = (0..=u8::MAX).map(|i| format!("n{};", i)).collect::<String>();
+        assert!(Graph::<String, String, u8>::from_dot(&format!("graph {{ {} }}", ids)).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Typed node and edge indices. Both are thin wrappers over an
+//! integer type implementing `IndexType`, so a node index can't be
+//! passed where an edge index is expected, and large graphs can
+//! store `u32` (or smaller) indices instead of `usize`.
+//!
+use std::{fmt, hash::Hash, iter, ops::Range};
+
+/// The index type used when none is specified
+pub type DefaultIx = usize;
+
+/// Every node index of a graph whose nodes are numbered `0..n`, in
+/// order
+pub type NodeIndices<Ix = DefaultIx> = iter::Map<Range<usize>, fn(usize) -> NodeIx<Ix>>;
+
+/// An integer type usable as the backing store of graph indices.
+pub trait IndexType: Copy + Default + Hash + Ord + fmt::Debug + 'static {
+
+    fn new(x: usize) -> Self;
+
+    fn index(&self) -> usize;
+
+    /// The largest representable value, reserved as the sentinel
+    /// marking the end of an edge list or an absent index
+    fn end() -> Self;
+}
+
+macro_rules! impl_index_type {
+    ($($t:ty),*) => {$(
+        impl IndexType for $t {
+            #[inline]
+            fn new(x: usize) -> Self {
+                debug_assert!(x <= <$t>::MAX as usize, "index out of range for {}", stringify!($t));
+                x as $t
+            }
+            #[inline]
+            fn index(&self) -> usize { *self as usize }
+            #[inline]
+            fn end() -> Self { <$t>::MAX }
+        }
+    )*}
+}
+
+impl_index_type!(u8, u16, u32, usize);
+
+macro_rules! index_newtype {
+    ($name:ident, $label:literal) => {
+        #[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<Ix = DefaultIx>(Ix);
+
+        impl<Ix: IndexType> $name<Ix> {
+
+            #[inline]
+            pub fn new(x: usize) -> Self {
+                $name(Ix::new(x))
+            }
+
+            #[inline]
+            pub fn index(self) -> usize {
+                self.0.index()
+            }
+
+            /// The sentinel index, see `IndexType::end`
+            #[inline]
+            pub fn end() -> Self {
+                $name(Ix::end())
+            }
+
+            #[inline]
+            pub fn is_end(self) -> bool {
+                self.0 == Ix::end()
+            }
+        }
+
+        impl<Ix: IndexType> From<Ix> for $name<Ix> {
+            fn from(ix: Ix) -> Self {
+                $name(ix)
+            }
+        }
+
+        impl<Ix: fmt::Debug> fmt::Debug for $name<Ix> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:?})", $label, self.0)
+            }
+        }
+
+        impl<Ix: IndexType> fmt::Display for $name<Ix> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.index())
+            }
+        }
+    };
+}
+
+index_newtype!(NodeIndex, "NodeIndex");
+index_newtype!(EdgeIndex, "EdgeIndex");
+
+pub type NodeIx<Ix = DefaultIx> = NodeIndex<Ix>;
+pub type EdgeIx<Ix = DefaultIx> = EdgeIndex<Ix>;
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn end_is_max() {
+        assert_eq!(NodeIndex::<u8>::end().index(), u8::MAX as usize);
+        assert!(EdgeIndex::<u32>::end().is_end());
+        assert!(!EdgeIndex::<u32>::new(3).is_end());
+        assert_eq!(NodeIx::new(7), NodeIndex::<usize>::from(7));
+    }
+}
@@ -0,0 +1,217 @@
+//! Per-node and per-edge data kept beside a `Graph`, for scratch
+//! space and algorithm results which don't belong in the weights.
+//!
+//! `Graph::remove` and `Graph::remove_edge` renumber by swapping the
+//! last node or edge into the hole, so the graph counts each change
+//! to its indices in a generation. A map remembers the generation it
+//! was made for, so a stale map is detected with `is_current` or
+//! `check` rather than silently misread. Mirroring a removal on the
+//! map with its own `remove` keeps it current. A map also remembers
+//! which graph it was made for, and is never current for another,
+//! even one of the same shape.
+//!
+//! ```
+//! use recollection::{algo::{page_rank, PageRankOptions}, data::{Graph, NodeMap}};
+//!
+//! let mut g = Graph::<&str, ()>::new_directed();
+//! let a = g.add("a");
+//! let b = g.add("b");
+//! let c = g.add("c");
+//! g.add_edge(a, b, ());
+//! g.add_edge(c, b, ());
+//! let mut rank = NodeMap::from_vec(&g, page_rank(&g, PageRankOptions::default()));
+//! assert!(rank[b] > rank[a]);
+//!
+//! g.remove(a);
+//! assert!(!rank.is_current(&g));
+//! rank.remove(a);
+//! assert!(rank.is_current(&g));
+//! // `c` was swapped into `a`'s place, and its rank went with it
+//! assert_eq!(g.weight(a), Some(&"c"));
+//! ```
+//!
+use std::{marker::PhantomData, ops};
+use crate::error::{RecolError, RecolResult};
+use super::{DefaultIx, EdgeIx, Graph, IndexType, NodeIx};
+
+macro_rules! attr_map {
+    ($name:ident, $ix:ident, $what:literal, $count:ident, $generation:ident, $remove:literal) => {
+        #[doc = concat!("A value for each ", $what, " of a `Graph`, indexed by `", stringify!($ix), "`")]
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $name<T, Ix = DefaultIx> {
+            values: Vec<T>,
+            graph: u64,
+            generation: u64,
+            ix: PhantomData<Ix>,
+        }
+
+        impl<T, Ix: IndexType> $name<T, Ix> {
+
+            #[doc = concat!("`value` for every ", $what, " of `g`")]
+            pub fn new<N: Clone, E: Clone>(g: &Graph<N, E, Ix>, value: T) -> Self
+            where
+                T: Clone,
+            {
+                Self::from_vec(g, vec![value; g.$count()])
+            }
+
+            #[doc = concat!("`f` of every ", $what, " of `g`")]
+            pub fn from_fn<N, E, F>(g: &Graph<N, E, Ix>, f: F) -> Self
+            where
+                N: Clone,
+                E: Clone,
+                F: FnMut($ix<Ix>) -> T,
+            {
+                Self::from_vec(g, (0..g.$count()).map($ix::new).map(f).collect())
+            }
+
+            #[doc = concat!("Wrap values already in ", $what, " index order, such as an")]
+            /// algorithm's results. Panics unless there is one for each.
+            pub fn from_vec<N: Clone, E: Clone>(g: &Graph<N, E, Ix>, values: Vec<T>) -> Self {
+                assert_eq!(values.len(), g.$count(), concat!("one value per ", $what));
+                Self { values, graph: g.id, generation: g.$generation(), ix: PhantomData }
+            }
+
+            #[doc = concat!("Whether the map was made for `g`, and its ", $what, " indices")]
+            /// still mean what they did then. Items added since aren't
+            /// covered until `resize`d for.
+            pub fn is_current<N: Clone, E: Clone>(&self, g: &Graph<N, E, Ix>) -> bool {
+                self.graph == g.id && self.generation == g.$generation()
+            }
+
+            /// Like `is_current`, but fails with `RecolError::StaleMap`
+            pub fn check<N: Clone, E: Clone>(&self, g: &Graph<N, E, Ix>) -> RecolResult<()> {
+                if self.is_current(g) {
+                    Ok(())
+                } else {
+                    Err(RecolError::StaleMap)
+                }
+            }
+
+            #[doc = concat!("Fill in `value` for any ", $what, "s added to `g` since the map")]
+            /// was made
+            pub fn resize<N: Clone, E: Clone>(&mut self, g: &Graph<N, E, Ix>, value: T)
+            where
+                T: Clone,
+            {
+                self.values.resize(g.$count(), value);
+            }
+
+            #[doc = concat!("Mirror `", $remove, "`, moving the last value into the hole")]
+            /// so the map stays current. Returns the removed value.
+            pub fn remove(&mut self, i: $ix<Ix>) -> Option<T> {
+                if i.index() >= self.values.len() {
+                    return None;
+                }
+                self.generation += 1;
+                Some(self.values.swap_remove(i.index()))
+            }
+
+            pub fn get(&self, i: $ix<Ix>) -> Option<&T> {
+                self.values.get(i.index())
+            }
+
+            pub fn get_mut(&mut self, i: $ix<Ix>) -> Option<&mut T> {
+                self.values.get_mut(i.index())
+            }
+
+            pub fn len(&self) -> usize { self.values.len() }
+
+            pub fn is_empty(&self) -> bool { self.values.is_empty() }
+
+            pub fn iter(&self) -> impl Iterator<Item = ($ix<Ix>, &T)> {
+                self.values.iter().enumerate().map(|(i, v)| ($ix::new(i), v))
+            }
+
+            pub fn values(&self) -> &[T] { &self.values }
+
+            pub fn into_vec(self) -> Vec<T> { self.values }
+        }
+
+        impl<T, Ix: IndexType> ops::Index<$ix<Ix>> for $name<T, Ix> {
+            type Output = T;
+
+            fn index(&self, i: $ix<Ix>) -> &T {
+                &self.values[i.index()]
+            }
+        }
+
+        impl<T, Ix: IndexType> ops::IndexMut<$ix<Ix>> for $name<T, Ix> {
+            fn index_mut(&mut self, i: $ix<Ix>) -> &mut T {
+                &mut self.values[i.index()]
+            }
+        }
+    };
+}
+
+attr_map!(NodeMap, NodeIx, "node", node_count, node_generation, "Graph::remove");
+attr_map!(EdgeMap, EdgeIx, "edge", edge_count, edge_generation, "Graph::remove_edge");
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn edge_map_follows_removals() {
+        let mut g = Graph::<(), u32>::new_undirected();
+        let n = (0..3).map(|_| g.add(())).collect::<Vec<_>>();
+        let ab = g.add_edge(n[0], n[1], 1);
+        g.add_edge(n[1], n[2], 2);
+        g.add_edge(n[2], n[0], 3);
+        let mut doubled = EdgeMap::from_fn(&g, |e| g.edge_weight(e).unwrap() * 2);
+        let degree = NodeMap::from_fn(&g, |v| g.neighbors(v).count());
+
+        g.remove_edge(ab);
+        assert!(doubled.check(&g).is_err());
+        assert!(degree.is_current(&g));
+        doubled.remove(ab);
+        doubled.check(&g).unwrap();
+        for (e, &w) in doubled.iter() {
+            assert_eq!(w, g.edge_weight(e).unwrap() * 2);
+        }
+
+        // a node removal takes its edges with it
+        g.remove(n[2]);
+        assert!(!degree.is_current(&g) && !doubled.is_current(&g));
+    }
+
+    #[test]
+    fn maps_cover_added_nodes_once_resized() {
+        let mut g = Graph::<(), ()>::new_directed();
+        let a = g.add(());
+        let mut seen = NodeMap::new(&g, false);
+        seen[a] = true;
+        let b = g.add(());
+        assert!(seen.is_current(&g));
+        assert_eq!(seen.get(b), None);
+        seen.resize(&g, false);
+        assert_eq!(seen.values(), &[true, false]);
+        g.clear();
+        assert!(!seen.is_current(&g));
+    }
+
+    #[test]
+    fn maps_belong_to_one_graph() {
+        let mut g = Graph::<(), u32>::new_directed();
+        let n = (0..3).map(|_| g.add(())).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], 1);
+        g.add_edge(n[0], n[1], 2);
+        g.add_edge(n[1], n[2], 3);
+        let degree = NodeMap::from_fn(&g, |v| g.neighbors(v).count());
+        let weight = EdgeMap::from_fn(&g, |e| *g.edge_weight(e).unwrap());
+        // same sizes and generations, but different graphs
+        let copy = g.map(|_, _| (), |_, &w| w);
+        assert!(!degree.is_current(&copy) && !weight.is_current(&copy));
+        assert!(!degree.is_current(&g.simple(|w, &other| *w += other)));
+
+        // retaining everything leaves the indices alone
+        g.retain_nodes(|_, _| true);
+        g.retain_edges(|_, _| true);
+        assert!(degree.is_current(&g) && weight.is_current(&g));
+        g.retain_edges(|_, &w| w != 2);
+        assert!(degree.is_current(&g) && !weight.is_current(&g));
+        g.retain_nodes(|v, _| v != n[2]);
+        assert!(!degree.is_current(&g));
+    }
+}
@@ -0,0 +1,153 @@
+//! Serde support for `Graph`, `Node` and `Edge`.
+//!
+//! Only weights, endpoints and the directed flag are stored; the
+//! edge lists threaded through the nodes are rebuilt on load. A graph
+//! serializes as
+//!
+//! ```json
+//! { "directed": true, "nodes": [n0, n1], "edges": [[0, 1, e0]] }
+//! ```
+//!
+//! with each edge as `[source, target, weight]`.
+//!
+use serde::{de, ser::SerializeTuple, Deserialize, Deserializer, Serialize, Serializer};
+use super::{link_edge, Edge, EdgeIx, Graph, IndexType, Node, NodeIx};
+
+impl<N, Ix> Serialize for Node<N, Ix>
+where
+    N: Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.weight.serialize(serializer)
+    }
+}
+
+/// An unlinked node, as if just passed to `Graph::add`
+impl<'de, N, Ix> Deserialize<'de> for Node<N, Ix>
+where
+    N: Clone + Deserialize<'de>,
+    Ix: IndexType,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        N::deserialize(deserializer).map(Node::init)
+    }
+}
+
+impl<E, Ix> Serialize for Edge<E, Ix>
+where
+    E: Clone + Serialize,
+    Ix: IndexType,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(3)?;
+        tuple.serialize_element(&self.src().index())?;
+        tuple.serialize_element(&self.dest().index())?;
+        tuple.serialize_element(&self.weight)?;
+        tuple.end()
+    }
+}
+
+/// An unlinked edge, as if just passed to `Graph::add_edge`
+impl<'de, E, Ix> Deserialize<'de> for Edge<E, Ix>
+where
+    E: Clone + Deserialize<'de>,
+    Ix: IndexType,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (a, b, weight) = <(usize, usize, E)>::deserialize(deserializer)?;
+        for x in [a, b] {
+            if x >= Ix::end().index() {
+                return Err(de::Error::custom(format_args!("node index {} out of range", x)));
+            }
+        }
+        Ok(Edge::init(weight, NodeIx::new(a), NodeIx::new(b)))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(bound(serialize = "N: Serialize, E: Serialize, Ix: IndexType"))]
+struct GraphRef<'a, N: Clone, E: Clone, Ix> {
+    directed: bool,
+    nodes: &'a [Node<N, Ix>],
+    edges: &'a [Edge<E, Ix>],
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "N: Deserialize<'de>, E: Deserialize<'de>, Ix: IndexType"))]
+struct GraphRepr<N: Clone, E: Clone, Ix> {
+    directed: bool,
+    nodes: Vec<Node<N, Ix>>,
+    edges: Vec<Edge<E, Ix>>,
+}
+
+impl<N, E, Ix> Serialize for Graph<N, E, Ix>
+where
+    N: Clone + Serialize,
+    E: Clone + Serialize,
+    Ix: IndexType,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GraphRef {
+            directed: self.directed,
+            nodes: &self.nodes,
+            edges: &self.edges,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, N, E, Ix> Deserialize<'de> for Graph<N, E, Ix>
+where
+    N: Clone + Deserialize<'de>,
+    E: Clone + Deserialize<'de>,
+    Ix: IndexType,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let GraphRepr { directed, mut nodes, mut edges } = GraphRepr::<N, E, Ix>::deserialize(deserializer)?;
+        if nodes.len() >= Ix::end().index() || edges.len() >= Ix::end().index() {
+            return Err(de::Error::custom("graph too large for its index type"));
+        }
+        for (i, edge) in edges.iter_mut().enumerate() {
+            if !link_edge(&mut nodes, edge, EdgeIx::new(i)) {
+                let missing = edge.src().max(edge.dest());
+                return Err(de::Error::custom(format_args!(
+                    "edge {} points to missing node {}", i, missing.index())));
+            }
+        }
+        Ok(Graph { edges, nodes, directed, ..Default::default() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let mut g = Graph::<String, u32>::new_undirected();
+        let a = g.add("a".into());
+        let b = g.add("b".into());
+        let c = g.add("c".into());
+        g.add_edge(a, b, 1);
+        g.add_edge(b, c, 2);
+        g.add_edge(c, c, 3);
+        let json = serde_json::to_string(&g).unwrap();
+        assert_eq!(json, r#"{"directed":false,"nodes":["a","b","c"],"edges":[[0,1,1],[1,2,2],[2,2,3]]}"#);
+        let h: Graph<String, u32> = serde_json::from_str(&json).unwrap();
+        assert!(!h.is_directed());
+        assert_eq!(h.node_count(), 3);
+        let mut neighbors = h.neighbors(b).collect::<Vec<_>>();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![a, c]);
+        assert_eq!(h.get_edge(c, c), Some(EdgeIx::new(2)));
+    }
+
+    #[test]
+    fn missing_node_is_error() {
+        let json = r#"{"directed":true,"nodes":[1,2],"edges":[[0,1,null],[1,5,null]]}"#;
+        let err = serde_json::from_str::<Graph<u8, ()>>(json).unwrap_err();
+        assert!(err.to_string().contains("edge 1 points to missing node 5"));
+        let json = r#"{"directed":true,"nodes":[1,2],"edges":[[0,300,null]]}"#;
+        assert!(serde_json::from_str::<Graph<u8, (), u8>>(json).is_err());
+    }
+}
@@ -0,0 +1,583 @@
+//! A graph whose node and edge indices stay valid across removals.
+//!
+//! Where `Graph::remove` swaps the last node into the hole it leaves
+//! (renumbering it), `StableGraph` leaves the slot vacant and chains
+//! it into a free list which later insertions draw from. Indices only
+//! change when explicitly asked to with `compact`.
+//!
+use std::{fmt, mem};
+use crate::error::{RecolError, RecolResult};
+use crate::visit::{
+    Data, GraphBase, GraphProp, IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers,
+    NodeCount, NodeIndexable, Visitable,
+};
+use super::{
+    DefaultIx, Direction, Edge, EdgeIx, EdgeLink, Graph, IndexType, Linked,
+    Neighbors, Node, NodeIx, NodeLink, change_edge_links, find_edge, link_edge,
+};
+
+pub struct StableGraph<N, E, Ix = DefaultIx>
+where
+    N: Clone,
+    E: Clone,
+{
+    nodes: Vec<Node<Option<N>, Ix>>,
+    edges: Vec<Edge<Option<E>, Ix>>,
+    node_count: usize,
+    edge_count: usize,
+    /// Head of the vacant node list, chained through `edges.outgoing`
+    free_node: NodeIx<Ix>,
+    /// Head of the vacant edge list, chained through `next.outgoing`
+    free_edge: EdgeIx<Ix>,
+    directed: bool,
+}
+
+/// The old-to-new index mapping produced by `StableGraph::compact`.
+/// Indices which were vacant before compaction map to `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remapping<Ix = DefaultIx> {
+    pub nodes: Vec<Option<NodeIx<Ix>>>,
+    pub edges: Vec<Option<EdgeIx<Ix>>>,
+}
+
+impl<Ix: IndexType> Remapping<Ix> {
+
+    pub fn node(&self, old: NodeIx<Ix>) -> Option<NodeIx<Ix>> {
+        self.nodes.get(old.index()).copied().flatten()
+    }
+
+    pub fn edge(&self, old: EdgeIx<Ix>) -> Option<EdgeIx<Ix>> {
+        self.edges.get(old.index()).copied().flatten()
+    }
+}
+
+/// The neighbors of a node of a `StableGraph`, see
+/// `StableGraph::neighbors`
+#[derive(Debug)]
+pub struct StableNeighbors<'a, E: 'a, Ix: 'a = DefaultIx>
+where
+    E: Clone,
+    Ix: IndexType
+{
+    iter: Neighbors<'a, Option<E>, Ix>,
+}
+
+impl<E, Ix> Iterator for StableNeighbors<'_, E, Ix>
+where
+    E: Clone,
+    Ix: IndexType
+{
+    type Item = NodeIx<Ix>;
+
+    fn next(&mut self) -> Option<NodeIx<Ix>> {
+        self.iter.next()
+    }
+}
+
+impl<N, E, Ix> Default for StableGraph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            node_count: 0,
+            edge_count: 0,
+            free_node: NodeIx::end(),
+            free_edge: EdgeIx::end(),
+            directed: true,
+        }
+    }
+}
+
+impl<N, E, Ix> StableGraph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+
+    pub fn new_directed() -> Self {
+        Self { directed: true, ..Default::default() }
+    }
+
+    pub fn new_undirected() -> Self {
+        Self { directed: false, ..Default::default() }
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn node_count(&self) -> usize { self.node_count }
+
+    pub fn edge_count(&self) -> usize { self.edge_count }
+
+    /// One past the largest node index in use, vacant or not
+    pub fn node_bound(&self) -> usize { self.nodes.len() }
+
+    /// One past the largest edge index in use, vacant or not
+    pub fn edge_bound(&self) -> usize { self.edges.len() }
+
+    pub fn add(&mut self, weight: N) -> NodeIx<Ix> {
+        self.try_add(weight).expect("StableGraph::add")
+    }
+
+    /// Like `add`, but fails rather than panicking if the index type
+    /// can't address another node
+    pub fn try_add(&mut self, weight: N) -> RecolResult<NodeIx<Ix>> {
+        let node = Node::init(Some(weight));
+        if self.free_node.is_end() {
+            let ix = NodeIx::new(self.nodes.len());
+            if ix.is_end() {
+                return Err(RecolError::IndexOverflow);
+            }
+            self.nodes.push(node);
+            self.node_count += 1;
+            return Ok(ix);
+        }
+        let ix = self.free_node;
+        self.free_node = NodeIx::new(self.nodes[ix.index()].next_out().index());
+        self.nodes[ix.index()] = node;
+        self.node_count += 1;
+        Ok(ix)
+    }
+
+    pub fn add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> EdgeIx<Ix> {
+        self.try_add_edge(a, b, weight).expect("StableGraph::add_edge")
+    }
+
+    /// Like `add_edge`, but fails rather than panicking if either
+    /// endpoint is missing or the index type can't address another edge
+    pub fn try_add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
+        for n in [a, b] {
+            if !self.contains_node(n) {
+                return Err(RecolError::node_not_found(n));
+            }
+        }
+        let eix = if self.free_edge.is_end() {
+            EdgeIx::new(self.edges.len())
+        } else {
+            self.free_edge
+        };
+        if eix.is_end() {
+            return Err(RecolError::IndexOverflow);
+        }
+        let mut edge = Edge::init(Some(weight), a, b);
+        link_edge(&mut self.nodes, &mut edge, eix);
+        if eix.index() == self.edges.len() {
+            self.edges.push(edge);
+        } else {
+            self.free_edge = self.edges[eix.index()].next_out();
+            self.edges[eix.index()] = edge;
+        }
+        self.edge_count += 1;
+        Ok(eix)
+    }
+
+    /// Remove node `a` and every edge connected to it. All other
+    /// node and edge indices are left untouched.
+    pub fn remove(&mut self, a: NodeIx<Ix>) -> Option<N> {
+        if !self.contains_node(a) {
+            return None;
+        }
+        for dir in Direction::iter() {
+            loop {
+                let next = self.nodes[a.index()].next(&dir);
+                if next.is_end() { break; }
+                self.remove_edge(next);
+            }
+        }
+        let node = &mut self.nodes[a.index()];
+        let weight = node.weight.take();
+        node.edges = EdgeLink::new(EdgeIx::new(self.free_node.index()), EdgeIx::end());
+        self.free_node = a;
+        self.node_count -= 1;
+        weight
+    }
+
+    /// Like `remove`, but fails if `a` doesn't exist
+    pub fn try_remove(&mut self, a: NodeIx<Ix>) -> RecolResult<N> {
+        self.remove(a).ok_or_else(|| RecolError::node_not_found(a))
+    }
+
+    /// Remove edge `eix`. All other edge indices are left untouched.
+    pub fn remove_edge(&mut self, eix: EdgeIx<Ix>) -> Option<E> {
+        let (e_node, e_next) = match self.edges.get(eix.index()) {
+            Some(e) if e.weight.is_some() => (e.node, e.next.clone()),
+            _ => return None,
+        };
+        change_edge_links(&mut self.nodes, &mut self.edges, e_node, e_next, eix);
+        let edge = &mut self.edges[eix.index()];
+        let weight = edge.weight.take();
+        edge.node = NodeLink::default();
+        edge.next = EdgeLink::new(self.free_edge, EdgeIx::end());
+        self.free_edge = eix;
+        self.edge_count -= 1;
+        weight
+    }
+
+    /// Like `remove_edge`, but fails if `eix` doesn't exist
+    pub fn try_remove_edge(&mut self, eix: EdgeIx<Ix>) -> RecolResult<E> {
+        self.remove_edge(eix).ok_or_else(|| RecolError::edge_not_found(eix))
+    }
+
+    pub fn contains_node(&self, a: NodeIx<Ix>) -> bool {
+        self.weight(a).is_some()
+    }
+
+    pub fn weight(&self, a: NodeIx<Ix>) -> Option<&N> {
+        self.nodes.get(a.index()).and_then(|n| n.weight.as_ref())
+    }
+
+    pub fn weight_mut(&mut self, a: NodeIx<Ix>) -> Option<&mut N> {
+        self.nodes.get_mut(a.index()).and_then(|n| n.weight.as_mut())
+    }
+
+    pub fn edge_weight(&self, e: EdgeIx<Ix>) -> Option<&E> {
+        self.edges.get(e.index()).and_then(|e| e.weight.as_ref())
+    }
+
+    pub fn edge_weight_mut(&mut self, e: EdgeIx<Ix>) -> Option<&mut E> {
+        self.edges.get_mut(e.index()).and_then(|e| e.weight.as_mut())
+    }
+
+    pub fn edge_endpoints(&self, e: EdgeIx<Ix>) -> Option<(NodeIx<Ix>, NodeIx<Ix>)> {
+        match self.edges.get(e.index()) {
+            Some(e) if e.weight.is_some() => Some((e.node.src(), e.node.dest())),
+            _ => None,
+        }
+    }
+
+    pub fn update_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> EdgeIx<Ix> {
+        self.try_update_edge(a, b, weight).expect("StableGraph::update_edge")
+    }
+
+    /// Like `update_edge`, but fails rather than panicking if a new
+    /// edge is needed and can't be added
+    pub fn try_update_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
+        if let Some(ix) = self.get_edge(a, b) {
+            self.edges[ix.index()].weight = Some(weight);
+            return Ok(ix);
+        }
+        self.try_add_edge(a, b, weight)
+    }
+
+    pub fn get_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> Option<EdgeIx<Ix>> {
+        let n = self.nodes.get(a.index()).filter(|n| n.weight.is_some())?;
+        find_edge(&self.edges, &n.edges, b, self.directed).map(|(ix, _dir)| ix)
+    }
+
+    pub fn contains_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> bool {
+        self.get_edge(a, b).is_some()
+    }
+
+    pub fn neighbors(&self, a: NodeIx<Ix>) -> StableNeighbors<'_, E, Ix> {
+        self.neighbors_directed(a, Direction::Outgoing)
+    }
+
+    pub fn neighbors_directed(&self, a: NodeIx<Ix>, dir: Direction) -> StableNeighbors<'_, E, Ix> {
+        let mut iter = self.linked_neighbors(a);
+        if self.directed {
+            match dir {
+                Direction::Outgoing => iter.next.set_incoming(EdgeIx::end()),
+                Direction::Incoming => iter.next.set_outgoing(EdgeIx::end())
+            }
+            iter.src = NodeIx::end();
+        }
+        StableNeighbors { iter }
+    }
+
+    pub fn neighbors_undirected(&self, a: NodeIx<Ix>) -> StableNeighbors<'_, E, Ix> {
+        StableNeighbors { iter: self.linked_neighbors(a) }
+    }
+
+    /// The edge list walk behind the `neighbors` methods
+    fn linked_neighbors(&self, a: NodeIx<Ix>) -> Neighbors<'_, Option<E>, Ix> {
+        match self.nodes.get(a.index()) {
+            Some(n) if n.weight.is_some() => Neighbors::new(
+                a, &self.edges, n.edges.clone()),
+            _ => Neighbors::init(a, &self.edges),
+        }
+    }
+
+    /// Iterate over the indices of all occupied node slots
+    pub fn node_indices(&self) -> impl Iterator<Item = NodeIx<Ix>> + '_ {
+        self.nodes.iter()
+            .enumerate()
+            .filter(|(_, n)| n.weight.is_some())
+            .map(|(ix, _)| NodeIx::new(ix))
+    }
+
+    /// Iterate over the indices of all occupied edge slots
+    pub fn edge_indices(&self) -> impl Iterator<Item = EdgeIx<Ix>> + '_ {
+        self.edges.iter()
+            .enumerate()
+            .filter(|(_, e)| e.weight.is_some())
+            .map(|(ix, _)| EdgeIx::new(ix))
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.node_count = 0;
+        self.edge_count = 0;
+        self.free_node = NodeIx::end();
+        self.free_edge = EdgeIx::end();
+    }
+
+    pub fn clear_edges(&mut self) {
+        self.edges.clear();
+        self.edge_count = 0;
+        self.free_edge = EdgeIx::end();
+        for node in self.nodes.iter_mut().filter(|n| n.weight.is_some()) {
+            node.edges = EdgeLink::default();
+        }
+    }
+
+    /// Drop all vacant slots, renumbering the remaining nodes and
+    /// edges while keeping their relative order. Returns the mapping
+    /// from old indices to new ones.
+    pub fn compact(&mut self) -> Remapping<Ix> {
+        let old_nodes = mem::take(&mut self.nodes);
+        let old_edges = mem::take(&mut self.edges);
+        self.clear();
+        let nodes = old_nodes.into_iter()
+            .map(|n| n.weight.map(|w| self.add(w)))
+            .collect::<Vec<_>>();
+        let edges = old_edges.into_iter()
+            .map(|e| {
+                let (a, b) = (e.node.src(), e.node.dest());
+                e.weight.map(|w| self.add_edge(
+                    nodes[a.index()].unwrap(), nodes[b.index()].unwrap(), w))
+            })
+            .collect();
+        Remapping { nodes, edges }
+    }
+}
+
+impl<N, E, Ix> From<Graph<N, E, Ix>> for StableGraph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+    fn from(g: Graph<N, E, Ix>) -> Self {
+        let node_count = g.nodes.len();
+        let edge_count = g.edges.len();
+        Self {
+            nodes: g.nodes.into_iter()
+                .map(|n| Node { weight: Some(n.weight), edges: n.edges })
+                .collect(),
+            edges: g.edges.into_iter()
+                .map(|e| Edge { weight: Some(e.weight), node: e.node, next: e.next })
+                .collect(),
+            node_count,
+            edge_count,
+            directed: g.directed,
+            ..Default::default()
+        }
+    }
+}
+
+impl<N, E, Ix> From<StableGraph<N, E, Ix>> for Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+    /// Vacant slots are dropped, so indices are renumbered exactly
+    /// as by `StableGraph::compact`
+    fn from(mut g: StableGraph<N, E, Ix>) -> Self {
+        g.compact();
+        Self {
+            nodes: g.nodes.into_iter()
+                .map(|n| Node { weight: n.weight.unwrap(), edges: n.edges })
+                .collect(),
+            edges: g.edges.into_iter()
+                .map(|e| Edge { weight: e.weight.unwrap(), node: e.node, next: e.next })
+                .collect(),
+            directed: g.directed,
+            ..Default::default()
+        }
+    }
+}
+
+impl<N, E, Ix> fmt::Debug for StableGraph<N, E, Ix>
+where
+    N: fmt::Debug + Clone,
+    E: fmt::Debug + Clone,
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fmt_struct = f.debug_struct("StableGraph");
+        fmt_struct.field("# nodes", &self.node_count());
+        fmt_struct.field("# edges", &self.edge_count());
+        if self.node_count() > 0 {
+            fmt_struct.field("Nodes", &self.nodes);
+        }
+        if self.edge_count() > 0 {
+            fmt_struct.field("Edges", &self.edges);
+        }
+        fmt_struct.finish()
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphBase for StableGraph<N, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Data for StableGraph<N, E, Ix> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphProp for StableGraph<N, E, Ix> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> NodeCount for StableGraph<N, E, Ix> {
+    fn node_count(&self) -> usize {
+        self.node_count
+    }
+}
+
+/// Vacant slots keep their indices, so `node_bound` may exceed
+/// `node_count`
+impl<N: Clone, E: Clone, Ix: IndexType> NodeIndexable for StableGraph<N, E, Ix> {
+    fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn to_index(&self, n: NodeIx<Ix>) -> usize {
+        n.index()
+    }
+
+    fn from_index(&self, i: usize) -> NodeIx<Ix> {
+        NodeIx::new(i)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Visitable for StableGraph<N, E, Ix> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.nodes.len()]
+    }
+
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.nodes.len(), false);
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoNeighbors for &'a StableGraph<N, E, Ix> {
+    type Neighbors = StableNeighbors<'a, E, Ix>;
+
+    fn neighbors(self, n: NodeIx<Ix>) -> Self::Neighbors {
+        StableGraph::neighbors(self, n)
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoNeighborsDirected for &'a StableGraph<N, E, Ix> {
+    type NeighborsDirected = StableNeighbors<'a, E, Ix>;
+
+    fn neighbors_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::NeighborsDirected {
+        StableGraph::neighbors_directed(self, n, dir)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> IntoNodeIdentifiers for &StableGraph<N, E, Ix> {
+    type NodeIdentifiers = std::vec::IntoIter<NodeIx<Ix>>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        self.node_indices().collect::<Vec<_>>().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::visit::{Bfs, Dfs};
+
+    #[test]
+    fn remove_keeps_indices() {
+        let mut g = StableGraph::<&str, usize>::new_directed();
+        let a = g.add("a");
+        let b = g.add("b");
+        let c = g.add("c");
+        let ab = g.add_edge(a, b, 1);
+        let bc = g.add_edge(b, c, 2);
+        let ca = g.add_edge(c, a, 3);
+        assert_eq!(g.remove(a), Some("a"));
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.weight(c), Some(&"c"));
+        assert_eq!(g.edge_endpoints(bc), Some((b, c)));
+        assert_eq!(g.edge_weight(ab), None);
+        assert_eq!(g.edge_weight(ca), None);
+        assert_eq!(g.neighbors(b).collect::<Vec<_>>(), vec![c]);
+        assert_eq!(g.neighbors(c).count(), 0);
+    }
+
+    #[test]
+    fn walks_around_vacant_slots() {
+        let mut g = StableGraph::<(), ()>::new_directed();
+        let n = (0..4).map(|_| g.add(())).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], ());
+        g.add_edge(n[1], n[3], ());
+        g.add_edge(n[0], n[2], ());
+        g.remove(n[2]);
+        let mut bfs = Bfs::new(&g, n[0]);
+        let mut order = vec![];
+        while let Some(v) = bfs.next(&g) {
+            order.push(v);
+        }
+        assert_eq!(order, vec![n[0], n[1], n[3]]);
+        let mut dfs = Dfs::with_direction(&g, n[3], Direction::Incoming);
+        assert_eq!(dfs.next(&g), Some(n[3]));
+        assert_eq!(dfs.next(&g), Some(n[1]));
+        assert_eq!(dfs.next(&g), Some(n[0]));
+        assert_eq!(dfs.next(&g), None);
+        assert_eq!((&g).node_identifiers().collect::<Vec<_>>(), vec![n[0], n[1], n[3]]);
+        assert_eq!((NodeCount::node_count(&g), g.node_bound()), (3, 4));
+    }
+
+    #[test]
+    fn vacant_slots_are_reused() {
+        let mut g = StableGraph::<usize, ()>::new_undirected();
+        let a = g.add(0);
+        let b = g.add(1);
+        let e = g.add_edge(a, b, ());
+        g.remove_edge(e);
+        g.remove(a);
+        assert_eq!(g.add(2), a);
+        assert_eq!(g.add_edge(a, b, ()), e);
+        assert_eq!(g.node_bound(), 2);
+        assert!(g.contains_edge(b, a));
+    }
+
+    #[test]
+    fn compact_remaps() {
+        let mut g = StableGraph::<usize, usize>::new_directed();
+        let n = (0..4).map(|i| g.add(i)).collect::<Vec<_>>();
+        let e01 = g.add_edge(n[0], n[1], 0);
+        let e23 = g.add_edge(n[2], n[3], 1);
+        let e31 = g.add_edge(n[3], n[1], 2);
+        g.remove(n[0]);
+        g.remove_edge(e23);
+        let map = g.compact();
+        let ix = NodeIx::new;
+        assert_eq!(map.nodes, vec![None, Some(ix(0)), Some(ix(1)), Some(ix(2))]);
+        assert_eq!(map.edge(e01), None);
+        assert_eq!(map.edge(e31), Some(EdgeIx::new(0)));
+        assert_eq!(g.edge_endpoints(EdgeIx::new(0)), Some((ix(2), ix(0))));
+        assert_eq!(g.node_bound(), 3);
+        assert_eq!(g.weight(ix(0)), Some(&1));
+    }
+}
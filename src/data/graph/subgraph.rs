@@ -0,0 +1,384 @@
+//! Cutting parts out of a `Graph`: as a new graph, with
+//! `induced_subgraph` and `ego_graph`, or as a `Subgraph` view which
+//! hides the rest of the graph without copying it.
+//!
+use std::collections::VecDeque;
+use crate::visit::{
+    Data, GraphBase, GraphProp, IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers,
+    NodeCount, NodeIndexable, Visitable,
+};
+use super::{DefaultIx, Direction, EdgeIx, EdgeRef, Graph, IndexType, Neighbors, NodeIx};
+
+/// A fixed size set of indices, one bit each
+#[derive(Debug, Clone)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn with_len(len: usize) -> Self {
+        Self { words: vec![0; len.div_ceil(64)] }
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.words.get(i / 64).is_some_and(|w| w >> (i % 64) & 1 == 1)
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn remove(&mut self, i: usize) {
+        if let Some(w) = self.words.get_mut(i / 64) {
+            *w &= !(1 << (i % 64));
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// A view of some of the nodes of a `Graph` and the edges between
+/// them, kept as bitsets over the graph's indices. Indices are those
+/// of the underlying graph; walking the view skips anything hidden.
+/// The walk traits are implemented for `&Subgraph`.
+///
+/// ```
+/// use recollection::data::Graph;
+///
+/// let mut g = Graph::<u32, ()>::new_undirected();
+/// let n = (0..4).map(|i| g.add(i)).collect::<Vec<_>>();
+/// for i in 0..3 {
+///     g.add_edge(n[i], n[i + 1], ());
+/// }
+/// let sub = g.subgraph(vec![n[0], n[1], n[3]]);
+/// assert_eq!(sub.neighbors(n[1]).collect::<Vec<_>>(), vec![n[0]]);
+/// assert_eq!(sub.edge_count(), 1);
+/// ```
+pub struct Subgraph<'a, N, E, Ix = DefaultIx>
+where
+    N: Clone,
+    E: Clone,
+{
+    graph: &'a Graph<N, E, Ix>,
+    nodes: BitSet,
+    edges: BitSet,
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Clone for Subgraph<'_, N, E, Ix> {
+    fn clone(&self) -> Self {
+        Self { graph: self.graph, nodes: self.nodes.clone(), edges: self.edges.clone() }
+    }
+}
+
+impl<'a, N, E, Ix> Subgraph<'a, N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+    /// The view of `nodes` and every edge between them. Indices not
+    /// in the graph are ignored.
+    pub fn new<I>(graph: &'a Graph<N, E, Ix>, nodes: I) -> Self
+    where
+        I: IntoIterator<Item = NodeIx<Ix>>,
+    {
+        let mut node_bits = BitSet::with_len(graph.node_count());
+        for n in nodes {
+            if n.index() < graph.node_count() {
+                node_bits.insert(n.index());
+            }
+        }
+        let mut edge_bits = BitSet::with_len(graph.edge_count());
+        for e in graph.edge_references() {
+            if node_bits.contains(e.source().index()) && node_bits.contains(e.target().index()) {
+                edge_bits.insert(e.id().index());
+            }
+        }
+        Self { graph, nodes: node_bits, edges: edge_bits }
+    }
+
+    pub fn graph(&self) -> &'a Graph<N, E, Ix> { self.graph }
+
+    pub fn is_directed(&self) -> bool { self.graph.is_directed() }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn edge_count(&self) -> usize { self.edges.len() }
+
+    pub fn includes_node(&self, n: NodeIx<Ix>) -> bool {
+        self.nodes.contains(n.index())
+    }
+
+    pub fn includes_edge(&self, e: EdgeIx<Ix>) -> bool {
+        self.edges.contains(e.index())
+    }
+
+    /// Hide `n` and the edges at it
+    pub fn hide_node(&mut self, n: NodeIx<Ix>) {
+        if !self.includes_node(n) {
+            return;
+        }
+        self.nodes.remove(n.index());
+        let mut edges = self.graph.neighbors_undirected(n);
+        while let Some((e, _)) = edges.next_edge() {
+            self.edges.remove(e.index());
+        }
+    }
+
+    /// Hide `e`, leaving its endpoints
+    pub fn hide_edge(&mut self, e: EdgeIx<Ix>) {
+        self.edges.remove(e.index());
+    }
+
+    /// The visible nodes, in index order
+    pub fn node_indices(&self) -> impl Iterator<Item = NodeIx<Ix>> + '_ {
+        self.graph.node_indices().filter(move |&n| self.includes_node(n))
+    }
+
+    /// The visible edges, in index order
+    pub fn edge_references(&self) -> impl Iterator<Item = EdgeRef<'a, E, Ix>> + '_ {
+        self.graph.edge_references().filter(move |e| self.includes_edge(e.id()))
+    }
+
+    pub fn neighbors(&self, a: NodeIx<Ix>) -> SubNeighbors<'_, E, Ix> {
+        self.filter(a, self.graph.neighbors(a))
+    }
+
+    pub fn neighbors_directed(&self, a: NodeIx<Ix>, dir: Direction) -> SubNeighbors<'_, E, Ix> {
+        self.filter(a, self.graph.neighbors_directed(a, dir))
+    }
+
+    pub fn neighbors_undirected(&self, a: NodeIx<Ix>) -> SubNeighbors<'_, E, Ix> {
+        self.filter(a, self.graph.neighbors_undirected(a))
+    }
+
+    fn filter<'b>(&'b self, a: NodeIx<Ix>, iter: Neighbors<'b, E, Ix>) -> SubNeighbors<'b, E, Ix> {
+        let iter = self.includes_node(a).then_some(iter);
+        SubNeighbors { iter, edges: &self.edges }
+    }
+
+    /// Copy the view out into a new graph, along with the index in
+    /// `self.graph()` of each of its nodes. Nodes and edges keep
+    /// their relative order.
+    pub fn to_graph(&self) -> (Graph<N, E, Ix>, Vec<NodeIx<Ix>>) {
+        let g = self.graph.filter_map(
+            |n, w| self.includes_node(n).then(|| w.clone()),
+            |e, w| self.includes_edge(e).then(|| w.clone()));
+        (g, self.node_indices().collect())
+    }
+}
+
+/// The neighbors of a node in a `Subgraph`, over visible edges
+#[derive(Debug)]
+pub struct SubNeighbors<'a, E, Ix = DefaultIx>
+where
+    E: Clone,
+    Ix: IndexType
+{
+    iter: Option<Neighbors<'a, E, Ix>>,
+    edges: &'a BitSet,
+}
+
+impl<'a, E, Ix> Iterator for SubNeighbors<'a, E, Ix>
+where
+    E: Clone,
+    Ix: IndexType
+{
+    type Item = NodeIx<Ix>;
+
+    fn next(&mut self) -> Option<NodeIx<Ix>> {
+        let iter = self.iter.as_mut()?;
+        // a visible edge has both ends visible
+        while let Some((e, n)) = iter.next_edge() {
+            if self.edges.contains(e.index()) {
+                return Some(n);
+            }
+        }
+        None
+    }
+}
+
+impl<N, E, Ix> Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType
+{
+    /// A view of `nodes` and the edges between them
+    pub fn subgraph<I>(&self, nodes: I) -> Subgraph<'_, N, E, Ix>
+    where
+        I: IntoIterator<Item = NodeIx<Ix>>,
+    {
+        Subgraph::new(self, nodes)
+    }
+
+    /// A new graph of `nodes` and the edges between them, along with
+    /// the index in `self` of each of its nodes. Nodes and edges keep
+    /// their relative order; duplicates and missing indices in
+    /// `nodes` are ignored.
+    pub fn induced_subgraph(&self, nodes: &[NodeIx<Ix>]) -> (Graph<N, E, Ix>, Vec<NodeIx<Ix>>) {
+        self.subgraph(nodes.iter().copied()).to_graph()
+    }
+
+    /// The subgraph induced by the nodes within `radius` steps of
+    /// `center` along edges in direction `dir`, as for
+    /// `induced_subgraph`. Edges of an undirected graph are followed
+    /// either way.
+    pub fn ego_graph(&self, center: NodeIx<Ix>, radius: usize, dir: Direction) -> (Graph<N, E, Ix>, Vec<NodeIx<Ix>>) {
+        let mut dist = vec![usize::MAX; self.node_count()];
+        let mut queue = VecDeque::new();
+        if center.index() < self.node_count() {
+            dist[center.index()] = 0;
+            queue.push_back(center);
+        }
+        while let Some(a) = queue.pop_front() {
+            let d = dist[a.index()];
+            if d == radius {
+                continue;
+            }
+            for b in self.neighbors_directed(a, dir) {
+                if dist[b.index()] == usize::MAX {
+                    dist[b.index()] = d + 1;
+                    queue.push_back(b);
+                }
+            }
+        }
+        self.subgraph(self.node_indices().filter(|n| dist[n.index()] != usize::MAX)).to_graph()
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphBase for Subgraph<'_, N, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Data for Subgraph<'_, N, E, Ix> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphProp for Subgraph<'_, N, E, Ix> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> NodeCount for Subgraph<'_, N, E, Ix> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> NodeIndexable for Subgraph<'_, N, E, Ix> {
+    fn node_bound(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn to_index(&self, n: NodeIx<Ix>) -> usize {
+        n.index()
+    }
+
+    fn from_index(&self, i: usize) -> NodeIx<Ix> {
+        NodeIx::new(i)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Visitable for Subgraph<'_, N, E, Ix> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.graph.node_count()]
+    }
+
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.graph.node_count(), false);
+    }
+}
+
+impl<'b, N: Clone, E: Clone, Ix: IndexType> IntoNeighbors for &'b Subgraph<'_, N, E, Ix> {
+    type Neighbors = SubNeighbors<'b, E, Ix>;
+
+    fn neighbors(self, n: NodeIx<Ix>) -> Self::Neighbors {
+        Subgraph::neighbors(self, n)
+    }
+}
+
+impl<'b, N: Clone, E: Clone, Ix: IndexType> IntoNeighborsDirected for &'b Subgraph<'_, N, E, Ix> {
+    type NeighborsDirected = SubNeighbors<'b, E, Ix>;
+
+    fn neighbors_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::NeighborsDirected {
+        Subgraph::neighbors_directed(self, n, dir)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> IntoNodeIdentifiers for &Subgraph<'_, N, E, Ix> {
+    type NodeIdentifiers = std::vec::IntoIter<NodeIx<Ix>>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        self.node_indices().collect::<Vec<_>>().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::visit::Bfs;
+
+    /// 0 -> 1 -> 2 -> 3, and 0 -> 4
+    fn chain() -> (Graph<usize, &'static str>, Vec<NodeIx>) {
+        let mut g = Graph::new_directed();
+        let n = (0..5).map(|i| g.add(i)).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], "01");
+        g.add_edge(n[1], n[2], "12");
+        g.add_edge(n[2], n[3], "23");
+        g.add_edge(n[0], n[4], "04");
+        (g, n)
+    }
+
+    #[test]
+    fn induced_and_ego() {
+        let (g, n) = chain();
+        let (sub, map) = g.induced_subgraph(&[n[3], n[1], n[2], n[1]]);
+        assert_eq!(map, vec![n[1], n[2], n[3]]);
+        let edges = sub.edge_references().map(|e| *e.weight()).collect::<Vec<_>>();
+        assert_eq!(edges, vec!["12", "23"]);
+        assert!(sub.contains_edge(NodeIx::new(0), NodeIx::new(1)));
+
+        let (ego, map) = g.ego_graph(n[1], 1, Direction::Outgoing);
+        assert_eq!(map, vec![n[1], n[2]]);
+        assert_eq!(ego.edge_count(), 1);
+        let (ego, map) = g.ego_graph(n[1], 1, Direction::Incoming);
+        assert_eq!(map, vec![n[0], n[1]]);
+        assert_eq!(ego.weight(NodeIx::new(0)), Some(&0));
+        assert_eq!(g.ego_graph(n[0], 2, Direction::Outgoing).1.len(), 4);
+        assert_eq!(g.ego_graph(NodeIx::new(9), 2, Direction::Outgoing).0.node_count(), 0);
+    }
+
+    #[test]
+    fn subgraph_view_filters() {
+        let (g, n) = chain();
+        let mut sub = g.subgraph(vec![n[0], n[1], n[2], n[4]]);
+        assert_eq!((sub.node_count(), sub.edge_count()), (4, 3));
+        let mut out = sub.neighbors(n[0]).collect::<Vec<_>>();
+        out.sort();
+        assert_eq!(out, vec![n[1], n[4]]);
+        assert_eq!(sub.neighbors(n[2]).count(), 0);
+        assert_eq!(sub.neighbors(n[3]).count(), 0);
+
+        sub.hide_edge(EdgeIx::new(0));
+        assert_eq!(sub.neighbors_undirected(n[1]).collect::<Vec<_>>(), vec![n[2]]);
+        sub.hide_node(n[4]);
+        assert_eq!(sub.edge_count(), 1);
+        let mut bfs = Bfs::new(&sub, n[0]);
+        assert_eq!(bfs.next(&sub), Some(n[0]));
+        assert_eq!(bfs.next(&sub), None);
+
+        let (copy, map) = sub.to_graph();
+        assert_eq!(map, vec![n[0], n[1], n[2]]);
+        assert_eq!(copy.edge_references().map(|e| *e.weight()).collect::<Vec<_>>(), vec!["12"]);
+    }
+}
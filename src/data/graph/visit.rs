@@ -0,0 +1,122 @@
+//! The `visit` traits for `Graph`.
+//!
+use crate::visit::{
+    Data, EdgeReference, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoEdgesDirected,
+    IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount, NodeIndexable, Visitable,
+};
+use super::{
+    Direction, EdgeIx, EdgeRef, EdgeReferences, Edges, Graph, IndexType, Neighbors, NodeIndices,
+    NodeIx,
+};
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphBase for Graph<N, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Data for Graph<N, E, Ix> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> GraphProp for Graph<N, E, Ix> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> NodeCount for Graph<N, E, Ix> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> NodeIndexable for Graph<N, E, Ix> {
+    fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn to_index(&self, n: NodeIx<Ix>) -> usize {
+        n.index()
+    }
+
+    fn from_index(&self, i: usize) -> NodeIx<Ix> {
+        NodeIx::new(i)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> Visitable for Graph<N, E, Ix> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.nodes.len()]
+    }
+
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.nodes.len(), false);
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoNeighbors for &'a Graph<N, E, Ix> {
+    type Neighbors = Neighbors<'a, E, Ix>;
+
+    fn neighbors(self, n: NodeIx<Ix>) -> Self::Neighbors {
+        Graph::neighbors(self, n)
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoNeighborsDirected for &'a Graph<N, E, Ix> {
+    type NeighborsDirected = Neighbors<'a, E, Ix>;
+
+    fn neighbors_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::NeighborsDirected {
+        Graph::neighbors_directed(self, n, dir)
+    }
+}
+
+impl<N: Clone, E: Clone, Ix: IndexType> IntoNodeIdentifiers for &Graph<N, E, Ix> {
+    type NodeIdentifiers = NodeIndices<Ix>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        (0..self.nodes.len()).map(NodeIx::new)
+    }
+}
+
+impl<'a, E, Ix: IndexType> EdgeReference for EdgeRef<'a, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+    type Weight = E;
+
+    fn source(&self) -> NodeIx<Ix> { self.node.src }
+
+    fn target(&self) -> NodeIx<Ix> { self.node.dest }
+
+    fn weight(&self) -> &E { self.weight }
+
+    fn id(&self) -> EdgeIx<Ix> { self.ix }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoEdgeReferences for &'a Graph<N, E, Ix> {
+    type EdgeRef = EdgeRef<'a, E, Ix>;
+    type EdgeReferences = EdgeReferences<'a, E, Ix>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        Graph::edge_references(self)
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoEdges for &'a Graph<N, E, Ix> {
+    type Edges = Edges<'a, E, Ix>;
+
+    fn edges(self, n: NodeIx<Ix>) -> Self::Edges {
+        Graph::edges_directed(self, n, Direction::Outgoing)
+    }
+}
+
+impl<'a, N: Clone, E: Clone, Ix: IndexType> IntoEdgesDirected for &'a Graph<N, E, Ix> {
+    type EdgesDirected = Edges<'a, E, Ix>;
+
+    fn edges_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::EdgesDirected {
+        Graph::edges_directed(self, n, dir)
+    }
+}
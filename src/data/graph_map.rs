@@ -0,0 +1,483 @@
+//! A graph whose nodes are addressed by their own value, such as an
+//! id or a string, rather than by an index that can shift when
+//! another node is removed.
+//!
+use std::collections::{btree_map, hash_map, BTreeMap, HashMap, HashSet};
+use std::{fmt, hash::Hash, iter};
+use crate::data::graph::{Direction, Graph, IndexType, NodeIx};
+use crate::visit::{
+    Data, EdgeReference, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoEdgesDirected,
+    IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount, Visitable,
+};
+
+/// Which edges join a node to one of its neighbors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Adjacent {
+    outgoing: bool,
+    incoming: bool,
+}
+
+impl Adjacent {
+    fn has(&self, dir: Direction) -> bool {
+        match dir {
+            Direction::Outgoing => self.outgoing,
+            Direction::Incoming => self.incoming,
+        }
+    }
+
+    fn set(&mut self, dir: Direction, value: bool) {
+        match dir {
+            Direction::Outgoing => self.outgoing = value,
+            Direction::Incoming => self.incoming = value,
+        }
+    }
+}
+
+/// A graph keyed by node value. Each node keeps its neighbors in an
+/// ordered map, so neighbors and edges are visited in key order, and
+/// edge weights are kept in one map keyed by endpoint pair. A pair of
+/// nodes holds at most one edge.
+///
+/// Keys must be `Ord` as well as `Hash` for those ordered maps.
+/// Nodes themselves are hashed, so `nodes` yields them in no
+/// particular order.
+///
+/// ```
+/// use recollection::data::GraphMap;
+///
+/// let mut g = GraphMap::new_undirected();
+/// g.add_edge("a", "b", 1);
+/// g.add_edge("c", "b", 2);
+/// g.remove_node("a");
+/// assert_eq!(g.neighbors("b").collect::<Vec<_>>(), vec!["c"]);
+/// assert_eq!(g.edge_weight("b", "c"), Some(&2));
+/// ```
+#[derive(Clone)]
+pub struct GraphMap<K, E> {
+    nodes: HashMap<K, BTreeMap<K, Adjacent>>,
+    edges: BTreeMap<(K, K), E>,
+    directed: bool,
+}
+
+impl<K, E> Default for GraphMap<K, E> {
+    fn default() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            edges: BTreeMap::new(),
+            directed: true,
+        }
+    }
+}
+
+impl<K: fmt::Debug, E: fmt::Debug> fmt::Debug for GraphMap<K, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphMap")
+            .field("directed", &self.directed)
+            .field("nodes", &self.nodes.keys().collect::<Vec<_>>())
+            .field("edges", &self.edges)
+            .finish()
+    }
+}
+
+impl<K, E> GraphMap<K, E>
+where
+    K: Copy + Ord + Hash,
+{
+
+    pub fn new_directed() -> Self {
+        Self { directed: true, ..Default::default() }
+    }
+
+    pub fn new_undirected() -> Self {
+        Self { directed: false, ..Default::default() }
+    }
+
+    /// Build from a list of edges, adding their endpoints as nodes
+    pub fn from_edges<I>(directed: bool, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (K, K, E)>,
+    {
+        let mut g = if directed { Self::new_directed() } else { Self::new_undirected() };
+        for (a, b, weight) in edges {
+            g.add_edge(a, b, weight);
+        }
+        g
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn edge_count(&self) -> usize { self.edges.len() }
+
+    /// The key an edge's weight is stored under: sorted if undirected
+    fn edge_key(&self, a: K, b: K) -> (K, K) {
+        if self.directed || a <= b { (a, b) } else { (b, a) }
+    }
+
+    /// Add node `k` if it isn't already present
+    pub fn add_node(&mut self, k: K) -> K {
+        self.nodes.entry(k).or_default();
+        k
+    }
+
+    pub fn contains_node(&self, k: K) -> bool {
+        self.nodes.contains_key(&k)
+    }
+
+    /// Remove `k` and every edge touching it. Returns `false` if it
+    /// wasn't present.
+    pub fn remove_node(&mut self, k: K) -> bool {
+        let adjacent = match self.nodes.remove(&k) {
+            Some(adjacent) => adjacent,
+            None => return false,
+        };
+        for (n, _) in adjacent {
+            if let Some(links) = self.nodes.get_mut(&n) {
+                links.remove(&k);
+            }
+            self.edges.remove(&(k, n));
+            self.edges.remove(&(n, k));
+        }
+        true
+    }
+
+    /// Add an edge from `a` to `b`, adding either node if missing.
+    /// Returns the weight of the edge it replaces, if any.
+    pub fn add_edge(&mut self, a: K, b: K, weight: E) -> Option<E> {
+        let key = self.edge_key(a, b);
+        let old = self.edges.insert(key, weight);
+        if old.is_none() {
+            let directed = self.directed;
+            let mut link = |from: K, to: K, dir: Direction| {
+                let adjacent = self.nodes.entry(from).or_default().entry(to).or_default();
+                if directed {
+                    adjacent.set(dir, true);
+                } else {
+                    *adjacent = Adjacent { outgoing: true, incoming: true };
+                }
+            };
+            link(a, b, Direction::Outgoing);
+            link(b, a, Direction::Incoming);
+        }
+        old
+    }
+
+    pub fn remove_edge(&mut self, a: K, b: K) -> Option<E> {
+        let key = self.edge_key(a, b);
+        let weight = self.edges.remove(&key)?;
+        let directed = self.directed;
+        let mut unlink = |from: K, to: K, dir: Direction| {
+            if let Some(links) = self.nodes.get_mut(&from) {
+                if let btree_map::Entry::Occupied(mut entry) = links.entry(to) {
+                    entry.get_mut().set(dir, false);
+                    if !directed || *entry.get() == Adjacent::default() {
+                        entry.remove();
+                    }
+                }
+            }
+        };
+        unlink(a, b, Direction::Outgoing);
+        unlink(b, a, Direction::Incoming);
+        Some(weight)
+    }
+
+    pub fn contains_edge(&self, a: K, b: K) -> bool {
+        self.edges.contains_key(&self.edge_key(a, b))
+    }
+
+    pub fn edge_weight(&self, a: K, b: K) -> Option<&E> {
+        self.edges.get(&self.edge_key(a, b))
+    }
+
+    pub fn edge_weight_mut(&mut self, a: K, b: K) -> Option<&mut E> {
+        let key = self.edge_key(a, b);
+        self.edges.get_mut(&key)
+    }
+
+    /// Every node, in no particular order
+    pub fn nodes(&self) -> Nodes<'_, K> {
+        Nodes { iter: self.nodes.keys() }
+    }
+
+    /// The successors of `k` if directed, all its neighbors if not,
+    /// in key order
+    pub fn neighbors(&self, k: K) -> MapNeighbors<'_, K> {
+        self.neighbors_directed(k, Direction::Outgoing)
+    }
+
+    /// The nodes across edges leaving `k` if `Outgoing`, entering it
+    /// if `Incoming`. Undirected graphs ignore `dir`.
+    pub fn neighbors_directed(&self, k: K, dir: Direction) -> MapNeighbors<'_, K> {
+        MapNeighbors { iter: self.nodes.get(&k).map(|links| links.iter()), dir }
+    }
+
+    /// The edges leaving `k`, or entering it if `dir` is `Incoming`.
+    /// Undirected edges are oriented so `k` is the source.
+    pub fn edges_directed(&self, k: K, dir: Direction) -> MapEdges<'_, K, E> {
+        MapEdges { g: self, node: k, neighbors: self.neighbors_directed(k, dir) }
+    }
+
+    pub fn edges(&self, k: K) -> MapEdges<'_, K, E> {
+        self.edges_directed(k, Direction::Outgoing)
+    }
+
+    /// Every edge, in key order
+    pub fn all_edges(&self) -> AllEdges<'_, K, E> {
+        AllEdges { iter: self.edges.iter() }
+    }
+
+    /// Copy into a `Graph`, numbering nodes in key order
+    pub fn to_graph<Ix: IndexType>(&self) -> Graph<K, E, Ix>
+    where
+        E: Clone,
+    {
+        let mut g = if self.directed { Graph::new_directed() } else { Graph::new_undirected() };
+        let mut keys = self.nodes.keys().copied().collect::<Vec<_>>();
+        keys.sort();
+        let ix = keys.iter()
+            .map(|&k| (k, g.add(k)))
+            .collect::<HashMap<K, NodeIx<Ix>>>();
+        for (&(a, b), weight) in &self.edges {
+            g.add_edge(ix[&a], ix[&b], weight.clone());
+        }
+        g
+    }
+}
+
+/// The nodes of a `GraphMap`
+#[derive(Debug, Clone)]
+pub struct Nodes<'a, K> {
+    iter: hash_map::Keys<'a, K, BTreeMap<K, Adjacent>>,
+}
+
+impl<'a, K: Copy> Iterator for Nodes<'a, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.iter.next().copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The neighbors of one node in a `GraphMap`
+#[derive(Debug, Clone)]
+pub struct MapNeighbors<'a, K> {
+    iter: Option<btree_map::Iter<'a, K, Adjacent>>,
+    dir: Direction,
+}
+
+impl<'a, K: Copy> Iterator for MapNeighbors<'a, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        let dir = self.dir;
+        self.iter.as_mut()?.find(|(_, adjacent)| adjacent.has(dir)).map(|(&k, _)| k)
+    }
+}
+
+/// A borrowed edge of a `GraphMap`
+#[derive(Debug)]
+pub struct MapEdgeRef<'a, K, E> {
+    id: (K, K),
+    source: K,
+    target: K,
+    weight: &'a E,
+}
+
+impl<'a, K: Copy, E> Clone for MapEdgeRef<'a, K, E> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, K: Copy, E> Copy for MapEdgeRef<'a, K, E> {}
+
+impl<'a, K: Copy + Ord, E> EdgeReference for MapEdgeRef<'a, K, E> {
+    type NodeId = K;
+    /// The endpoints the weight is stored under, sorted if undirected
+    type EdgeId = (K, K);
+    type Weight = E;
+
+    fn source(&self) -> K { self.source }
+
+    fn target(&self) -> K { self.target }
+
+    fn weight(&self) -> &E { self.weight }
+
+    fn id(&self) -> (K, K) { self.id }
+}
+
+/// The edges at one node of a `GraphMap`
+#[derive(Debug, Clone)]
+pub struct MapEdges<'a, K, E> {
+    g: &'a GraphMap<K, E>,
+    node: K,
+    neighbors: MapNeighbors<'a, K>,
+}
+
+impl<'a, K: Copy + Ord + Hash, E> Iterator for MapEdges<'a, K, E> {
+    type Item = MapEdgeRef<'a, K, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.neighbors.next()?;
+        let (source, target) = match self.neighbors.dir {
+            Direction::Incoming if self.g.directed => (other, self.node),
+            _ => (self.node, other),
+        };
+        let id = self.g.edge_key(source, target);
+        Some(MapEdgeRef { id, source, target, weight: &self.g.edges[&id] })
+    }
+}
+
+/// Every edge of a `GraphMap`
+#[derive(Debug, Clone)]
+pub struct AllEdges<'a, K, E> {
+    iter: btree_map::Iter<'a, (K, K), E>,
+}
+
+impl<'a, K: Copy, E> Iterator for AllEdges<'a, K, E> {
+    type Item = MapEdgeRef<'a, K, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(&id, weight)| MapEdgeRef { id, source: id.0, target: id.1, weight })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K: Copy + Ord + Hash + fmt::Debug, E> GraphBase for GraphMap<K, E> {
+    type NodeId = K;
+    type EdgeId = (K, K);
+}
+
+impl<K: Copy + Ord + Hash + fmt::Debug, E> Data for GraphMap<K, E> {
+    type NodeWeight = K;
+    type EdgeWeight = E;
+}
+
+impl<K: Copy + Ord + Hash + fmt::Debug, E> GraphProp for GraphMap<K, E> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<K: Copy + Ord + Hash + fmt::Debug, E> NodeCount for GraphMap<K, E> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<K: Copy + Ord + Hash + fmt::Debug, E> Visitable for GraphMap<K, E> {
+    type Map = HashSet<K>;
+
+    fn visit_map(&self) -> HashSet<K> {
+        HashSet::with_capacity(self.nodes.len())
+    }
+
+    fn reset_map(&self, map: &mut HashSet<K>) {
+        map.clear();
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoNeighbors for &'a GraphMap<K, E> {
+    type Neighbors = MapNeighbors<'a, K>;
+
+    fn neighbors(self, k: K) -> Self::Neighbors {
+        GraphMap::neighbors(self, k)
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoNeighborsDirected for &'a GraphMap<K, E> {
+    type NeighborsDirected = MapNeighbors<'a, K>;
+
+    fn neighbors_directed(self, k: K, dir: Direction) -> Self::NeighborsDirected {
+        GraphMap::neighbors_directed(self, k, dir)
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoNodeIdentifiers for &'a GraphMap<K, E> {
+    type NodeIdentifiers = Nodes<'a, K>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        self.nodes()
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoEdgeReferences for &'a GraphMap<K, E> {
+    type EdgeRef = MapEdgeRef<'a, K, E>;
+    type EdgeReferences = AllEdges<'a, K, E>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        self.all_edges()
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoEdges for &'a GraphMap<K, E> {
+    type Edges = MapEdges<'a, K, E>;
+
+    fn edges(self, k: K) -> Self::Edges {
+        GraphMap::edges(self, k)
+    }
+}
+
+impl<'a, K: Copy + Ord + Hash + fmt::Debug, E> IntoEdgesDirected for &'a GraphMap<K, E> {
+    type EdgesDirected = MapEdges<'a, K, E>;
+
+    fn edges_directed(self, k: K, dir: Direction) -> Self::EdgesDirected {
+        GraphMap::edges_directed(self, k, dir)
+    }
+}
+
+impl<K: Copy + Ord + Hash, E> iter::FromIterator<(K, K, E)> for GraphMap<K, E> {
+    /// A directed graph with the given edges
+    fn from_iter<I: IntoIterator<Item = (K, K, E)>>(edges: I) -> Self {
+        Self::from_edges(true, edges)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::algo::dijkstra;
+
+    #[test]
+    fn directed_map() {
+        let mut g = GraphMap::new_directed();
+        assert_eq!(g.add_edge("a", "b", 1), None);
+        g.add_edge("b", "a", 2);
+        g.add_edge("c", "a", 3);
+        g.add_edge("a", "a", 4);
+        assert_eq!(g.add_edge("a", "b", 5), Some(1));
+        assert_eq!((g.node_count(), g.edge_count()), (3, 4));
+        assert_eq!(g.neighbors("a").collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(g.neighbors_directed("a", Direction::Incoming).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(g.edges_directed("a", Direction::Incoming).map(|e| *e.weight).collect::<Vec<_>>(), vec![4, 2, 3]);
+
+        assert_eq!(g.remove_edge("a", "b"), Some(5));
+        assert!(!g.contains_edge("a", "b") && g.contains_edge("b", "a"));
+        assert_eq!(g.neighbors("a").collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(g.neighbors_directed("a", Direction::Incoming).count(), 3);
+
+        assert!(g.remove_node("a"));
+        assert!(!g.remove_node("a"));
+        assert_eq!((g.node_count(), g.edge_count()), (2, 0));
+        assert_eq!(g.neighbors("b").count(), 0);
+    }
+
+    #[test]
+    fn undirected_map_walks() {
+        let g = GraphMap::from_edges(false, vec![(1, 2, 4), (3, 2, 1), (1, 3, 7), (4, 3, 1)]);
+        assert_eq!(g.edge_weight(2, 3), Some(&1));
+        assert_eq!(g.edges(3).map(|e| e.target).collect::<Vec<_>>(), vec![1, 2, 4]);
+        let paths = dijkstra(&g, 1, None, |w| *w);
+        assert_eq!(paths.distance[&4], 6);
+        let graph = g.to_graph::<u32>();
+        assert_eq!((graph.node_count(), graph.edge_count()), (4, 4));
+        assert_eq!(graph.weight(NodeIx::new(3)), Some(&4));
+    }
+}
@@ -0,0 +1,702 @@
+//! A graph stored as an adjacency matrix. Checking for, or looking
+//! up, the edge between two nodes takes constant time, and there are
+//! no per-edge links to pay for, which suits dense graphs. A pair of
+//! nodes holds at most one edge.
+//!
+//! Cells are laid out so adding a node only appends to the store:
+//! node `m` of a directed graph owns the `2m + 1` cells of row and
+//! column `m` up to the diagonal, and node `m` of an undirected graph
+//! owns the `m + 1` cells of row `m` of the lower triangle. An edge's
+//! index is the position of its cell.
+//!
+use std::{convert::TryFrom, fmt, marker::PhantomData};
+use crate::data::graph::{DefaultIx, Direction, EdgeIx, Graph, IndexType, NodeIndices, NodeIx};
+use crate::error::{RecolError, RecolResult};
+use crate::visit::{
+    Data, EdgeReference, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoEdgesDirected,
+    IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount, NodeIndexable, Visitable,
+};
+
+/// Storage for the cells of a `MatrixGraph`, each holding at most
+/// one edge weight
+pub trait CellStore<E>: Default {
+    /// Grow or shrink to `len` cells. New cells are empty.
+    fn resize(&mut self, len: usize);
+
+    fn get(&self, i: usize) -> Option<&E>;
+
+    /// Store `value` in cell `i`, returning what it held
+    fn replace(&mut self, i: usize, value: Option<E>) -> Option<E>;
+}
+
+impl<E> CellStore<E> for Vec<Option<E>> {
+    fn resize(&mut self, len: usize) {
+        self.truncate(len);
+        self.resize_with(len, || None);
+    }
+
+    fn get(&self, i: usize) -> Option<&E> {
+        self[i].as_ref()
+    }
+
+    fn replace(&mut self, i: usize, value: Option<E>) -> Option<E> {
+        std::mem::replace(&mut self[i], value)
+    }
+}
+
+/// One bit per cell, for graphs whose edges carry no weight
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitCells {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitCells {
+    fn is_set(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+}
+
+impl CellStore<()> for BitCells {
+    fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(64), 0);
+        if len % 64 != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (len % 64)) - 1;
+        }
+        self.len = len;
+    }
+
+    fn get(&self, i: usize) -> Option<&()> {
+        assert!(i < self.len, "cell {} out of bounds", i);
+        self.is_set(i).then_some(&())
+    }
+
+    fn replace(&mut self, i: usize, value: Option<()>) -> Option<()> {
+        let old = self.get(i).copied();
+        match value {
+            Some(()) => self.words[i / 64] |= 1 << (i % 64),
+            None => self.words[i / 64] &= !(1 << (i % 64)),
+        }
+        old
+    }
+}
+
+/// The cell holding the edge from `a` to `b`
+fn cell(directed: bool, a: usize, b: usize) -> usize {
+    if directed {
+        let m = a.max(b);
+        if a == m { m * m + b } else { m * m + m + 1 + a }
+    } else {
+        let (hi, lo) = if a >= b { (a, b) } else { (b, a) };
+        hi * (hi + 1) / 2 + lo
+    }
+}
+
+/// The number of cells needed for `n` nodes
+fn cell_count(directed: bool, n: usize) -> usize {
+    if directed { n * n } else { n * (n + 1) / 2 }
+}
+
+/// The inverse of `cell`. Undirected edges are given larger end first.
+fn endpoints(directed: bool, i: usize) -> (usize, usize) {
+    if directed {
+        let m = isqrt(i);
+        let r = i - m * m;
+        if r <= m { (m, r) } else { (r - m - 1, m) }
+    } else {
+        let hi = (isqrt(8 * i + 1) - 1) / 2;
+        (hi, i - hi * (hi + 1) / 2)
+    }
+}
+
+fn isqrt(x: usize) -> usize {
+    let mut r = (x as f64).sqrt() as usize;
+    while r * r > x {
+        r -= 1;
+    }
+    while (r + 1) * (r + 1) <= x {
+        r += 1;
+    }
+    r
+}
+
+/// A graph backed by an adjacency matrix, sharing `Graph`'s index
+/// types and `Direction`. `S` stores the cells; see `BitMatrixGraph`
+/// for unweighted graphs.
+#[derive(Clone)]
+pub struct MatrixGraph<N, E, Ix = DefaultIx, S = Vec<Option<E>>> {
+    nodes: Vec<N>,
+    cells: S,
+    edge_count: usize,
+    directed: bool,
+    marker: PhantomData<(E, Ix)>,
+}
+
+/// A `MatrixGraph` spending a single bit on each pair of nodes
+pub type BitMatrixGraph<N, Ix = DefaultIx> = MatrixGraph<N, (), Ix, BitCells>;
+
+impl<N, E, Ix, S: CellStore<E>> Default for MatrixGraph<N, E, Ix, S> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            cells: S::default(),
+            edge_count: 0,
+            directed: true,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<N: fmt::Debug, E: fmt::Debug, Ix: IndexType, S: CellStore<E>> fmt::Debug for MatrixGraph<N, E, Ix, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let edges = self.edge_references()
+            .map(|e| (e.source, e.target, e.weight))
+            .collect::<Vec<_>>();
+        f.debug_struct("MatrixGraph")
+            .field("directed", &self.directed)
+            .field("nodes", &self.nodes)
+            .field("edges", &edges)
+            .finish()
+    }
+}
+
+impl<N, E, Ix, S> MatrixGraph<N, E, Ix, S>
+where
+    Ix: IndexType,
+    S: CellStore<E>,
+{
+
+    pub fn new_directed() -> Self {
+        Self { directed: true, ..Default::default() }
+    }
+
+    pub fn new_undirected() -> Self {
+        Self { directed: false, ..Default::default() }
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn edge_count(&self) -> usize { self.edge_count }
+
+    pub fn add(&mut self, weight: N) -> NodeIx<Ix> {
+        self.try_add(weight).expect("MatrixGraph::add")
+    }
+
+    /// Like `add`, but fails rather than panicking if the index type
+    /// can't address the new node's edges
+    pub fn try_add(&mut self, weight: N) -> RecolResult<NodeIx<Ix>> {
+        let n = self.nodes.len();
+        let len = cell_count(self.directed, n + 1);
+        if len > Ix::end().index() {
+            return Err(RecolError::IndexOverflow);
+        }
+        self.cells.resize(len);
+        self.nodes.push(weight);
+        Ok(NodeIx::new(n))
+    }
+
+    /// Add an edge from `a` to `b`, replacing the weight of the edge
+    /// already there, if any. Panics if either node is missing.
+    pub fn add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> EdgeIx<Ix> {
+        self.try_add_edge(a, b, weight).expect("MatrixGraph::add_edge")
+    }
+
+    /// Like `add_edge`, but fails rather than panicking if either
+    /// endpoint is missing
+    pub fn try_add_edge(&mut self, a: NodeIx<Ix>, b: NodeIx<Ix>, weight: E) -> RecolResult<EdgeIx<Ix>> {
+        for n in [a, b] {
+            if n.index() >= self.nodes.len() {
+                return Err(RecolError::node_not_found(n));
+            }
+        }
+        let i = cell(self.directed, a.index(), b.index());
+        if self.cells.replace(i, Some(weight)).is_none() {
+            self.edge_count += 1;
+        }
+        Ok(EdgeIx::new(i))
+    }
+
+    pub fn weight(&self, a: NodeIx<Ix>) -> Option<&N> {
+        self.nodes.get(a.index())
+    }
+
+    pub fn weight_mut(&mut self, a: NodeIx<Ix>) -> Option<&mut N> {
+        self.nodes.get_mut(a.index())
+    }
+
+    pub fn edge_weight(&self, e: EdgeIx<Ix>) -> Option<&E> {
+        if e.index() >= cell_count(self.directed, self.nodes.len()) {
+            return None;
+        }
+        self.cells.get(e.index())
+    }
+
+    /// The nodes `e` connects, larger index first if undirected
+    pub fn edge_endpoints(&self, e: EdgeIx<Ix>) -> Option<(NodeIx<Ix>, NodeIx<Ix>)> {
+        self.edge_weight(e)?;
+        let (a, b) = endpoints(self.directed, e.index());
+        Some((NodeIx::new(a), NodeIx::new(b)))
+    }
+
+    /// The edge from `a` to `b`, in constant time
+    pub fn get_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> Option<EdgeIx<Ix>> {
+        let n = self.nodes.len();
+        if a.index() >= n || b.index() >= n {
+            return None;
+        }
+        let i = cell(self.directed, a.index(), b.index());
+        self.cells.get(i).map(|_| EdgeIx::new(i))
+    }
+
+    pub fn contains_edge(&self, a: NodeIx<Ix>, b: NodeIx<Ix>) -> bool {
+        self.get_edge(a, b).is_some()
+    }
+
+    pub fn remove_edge(&mut self, e: EdgeIx<Ix>) -> Option<E> {
+        self.edge_weight(e)?;
+        self.edge_count -= 1;
+        self.cells.replace(e.index(), None)
+    }
+
+    /// Remove node `a` and its edges in time linear in the node
+    /// count. As with `Graph::remove`, the last node takes its index,
+    /// and that node's edges are renumbered.
+    pub fn remove(&mut self, a: NodeIx<Ix>) -> Option<N> {
+        let n = self.nodes.len();
+        let a = a.index();
+        if a >= n {
+            return None;
+        }
+        let directed = self.directed;
+        for b in 0..n {
+            for i in [cell(directed, a, b), cell(directed, b, a)] {
+                if self.cells.replace(i, None).is_some() {
+                    self.edge_count -= 1;
+                }
+            }
+        }
+        let last = n - 1;
+        if a != last {
+            for b in (0..n).filter(|&b| b != a) {
+                let nb = if b == last { a } else { b };
+                let w = self.cells.replace(cell(directed, last, b), None);
+                self.cells.replace(cell(directed, a, nb), w);
+                if directed && b != last {
+                    let w = self.cells.replace(cell(directed, b, last), None);
+                    self.cells.replace(cell(directed, nb, a), w);
+                }
+            }
+        }
+        self.cells.resize(cell_count(directed, last));
+        Some(self.nodes.swap_remove(a))
+    }
+
+    pub fn clear_edges(&mut self) {
+        self.cells = S::default();
+        self.cells.resize(cell_count(self.directed, self.nodes.len()));
+        self.edge_count = 0;
+    }
+
+    pub fn node_indices(&self) -> NodeIndices<Ix> {
+        (0..self.nodes.len()).map(NodeIx::new)
+    }
+
+    /// The successors of `a` if directed, all its neighbors if not
+    pub fn neighbors(&self, a: NodeIx<Ix>) -> MatrixNeighbors<'_, N, E, Ix, S> {
+        self.neighbors_directed(a, Direction::Outgoing)
+    }
+
+    /// The nodes across edges leaving `a` if `Outgoing`, entering it
+    /// if `Incoming`. Undirected graphs ignore `dir`.
+    pub fn neighbors_directed(&self, a: NodeIx<Ix>, dir: Direction) -> MatrixNeighbors<'_, N, E, Ix, S> {
+        MatrixNeighbors { edges: self.edges_directed(a, dir) }
+    }
+
+    /// The edges leaving `a` if `Outgoing`, entering it if
+    /// `Incoming`. Undirected edges are oriented so `a` is the source.
+    pub fn edges_directed(&self, a: NodeIx<Ix>, dir: Direction) -> MatrixEdges<'_, N, E, Ix, S> {
+        let next = if a.index() < self.nodes.len() { 0 } else { self.nodes.len() };
+        MatrixEdges { g: self, node: a.index(), next, dir }
+    }
+
+    pub fn edge_references(&self) -> MatrixEdgeReferences<'_, N, E, Ix, S> {
+        MatrixEdgeReferences { g: self, next: 0 }
+    }
+}
+
+impl<N, E, Ix: IndexType> MatrixGraph<N, E, Ix> {
+
+    pub fn edge_weight_mut(&mut self, e: EdgeIx<Ix>) -> Option<&mut E> {
+        self.cells.get_mut(e.index())?.as_mut()
+    }
+}
+
+/// A borrowed edge of a `MatrixGraph`
+pub struct MatrixEdgeRef<'a, E, Ix = DefaultIx> {
+    id: EdgeIx<Ix>,
+    source: NodeIx<Ix>,
+    target: NodeIx<Ix>,
+    weight: &'a E,
+}
+
+impl<'a, E, Ix: Copy> Clone for MatrixEdgeRef<'a, E, Ix> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, E, Ix: Copy> Copy for MatrixEdgeRef<'a, E, Ix> {}
+
+impl<'a, E: fmt::Debug, Ix: IndexType> fmt::Debug for MatrixEdgeRef<'a, E, Ix> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MatrixEdgeRef")
+            .field("id", &self.id)
+            .field("source", &self.source)
+            .field("target", &self.target)
+            .field("weight", self.weight)
+            .finish()
+    }
+}
+
+impl<'a, E, Ix: IndexType> EdgeReference for MatrixEdgeRef<'a, E, Ix> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+    type Weight = E;
+
+    fn source(&self) -> NodeIx<Ix> { self.source }
+
+    fn target(&self) -> NodeIx<Ix> { self.target }
+
+    fn weight(&self) -> &E { self.weight }
+
+    fn id(&self) -> EdgeIx<Ix> { self.id }
+}
+
+/// The edges at one node, found by scanning its row or column
+pub struct MatrixEdges<'a, N, E, Ix, S> {
+    g: &'a MatrixGraph<N, E, Ix, S>,
+    node: usize,
+    next: usize,
+    dir: Direction,
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> Iterator for MatrixEdges<'a, N, E, Ix, S> {
+    type Item = MatrixEdgeRef<'a, E, Ix>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let directed = self.g.directed;
+        while self.next < self.g.nodes.len() {
+            let other = self.next;
+            self.next += 1;
+            let (source, target) = match self.dir {
+                Direction::Incoming if directed => (other, self.node),
+                _ => (self.node, other),
+            };
+            let i = cell(directed, source, target);
+            if let Some(weight) = self.g.cells.get(i) {
+                return Some(MatrixEdgeRef {
+                    id: EdgeIx::new(i),
+                    source: NodeIx::new(source),
+                    target: NodeIx::new(target),
+                    weight,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// The nodes across the edges at one node
+pub struct MatrixNeighbors<'a, N, E, Ix, S> {
+    edges: MatrixEdges<'a, N, E, Ix, S>,
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> Iterator for MatrixNeighbors<'a, N, E, Ix, S> {
+    type Item = NodeIx<Ix>;
+
+    fn next(&mut self) -> Option<NodeIx<Ix>> {
+        let node = self.edges.node;
+        self.edges.next().map(|e| if e.source.index() == node { e.target } else { e.source })
+    }
+}
+
+/// Every edge, in order of cell
+pub struct MatrixEdgeReferences<'a, N, E, Ix, S> {
+    g: &'a MatrixGraph<N, E, Ix, S>,
+    next: usize,
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> Iterator for MatrixEdgeReferences<'a, N, E, Ix, S> {
+    type Item = MatrixEdgeRef<'a, E, Ix>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = cell_count(self.g.directed, self.g.nodes.len());
+        while self.next < len {
+            let i = self.next;
+            self.next += 1;
+            if let Some(weight) = self.g.cells.get(i) {
+                let (source, target) = endpoints(self.g.directed, i);
+                return Some(MatrixEdgeRef {
+                    id: EdgeIx::new(i),
+                    source: NodeIx::new(source),
+                    target: NodeIx::new(target),
+                    weight,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Copies the nodes and edges of `g`, keeping node indices. A pair
+/// of nodes holds one edge, so parallel edges of `g` silently
+/// collapse into one, keeping the weight of the last. Fails with
+/// `RecolError::IndexOverflow` if the index type can't address the
+/// cells of every node, which for `u8` or `u16` takes only a few
+/// dozen or a few hundred nodes.
+impl<N, E, Ix, S> TryFrom<&Graph<N, E, Ix>> for MatrixGraph<N, E, Ix, S>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    S: CellStore<E>,
+{
+    type Error = RecolError;
+
+    fn try_from(g: &Graph<N, E, Ix>) -> RecolResult<Self> {
+        let mut m = if g.is_directed() { Self::new_directed() } else { Self::new_undirected() };
+        for i in 0..g.node_count() {
+            m.try_add(g.weight(NodeIx::new(i)).unwrap().clone())?;
+        }
+        for e in g.edge_references() {
+            m.try_add_edge(e.source(), e.target(), e.weight().clone())?;
+        }
+        Ok(m)
+    }
+}
+
+/// Copies the nodes and edges of `m`, keeping node indices
+impl<N, E, Ix, S> From<&MatrixGraph<N, E, Ix, S>> for Graph<N, E, Ix>
+where
+    N: Clone,
+    E: Clone,
+    Ix: IndexType,
+    S: CellStore<E>,
+{
+    fn from(m: &MatrixGraph<N, E, Ix, S>) -> Self {
+        let mut g = if m.directed { Graph::new_directed() } else { Graph::new_undirected() };
+        for weight in &m.nodes {
+            g.add(weight.clone());
+        }
+        for e in m.edge_references() {
+            g.add_edge(e.source, e.target, e.weight.clone());
+        }
+        g
+    }
+}
+
+impl<N, E, Ix: IndexType, S> GraphBase for MatrixGraph<N, E, Ix, S> {
+    type NodeId = NodeIx<Ix>;
+    type EdgeId = EdgeIx<Ix>;
+}
+
+impl<N, E, Ix: IndexType, S> Data for MatrixGraph<N, E, Ix, S> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+
+impl<N, E, Ix: IndexType, S> GraphProp for MatrixGraph<N, E, Ix, S> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<N, E, Ix: IndexType, S> NodeCount for MatrixGraph<N, E, Ix, S> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<N, E, Ix: IndexType, S> NodeIndexable for MatrixGraph<N, E, Ix, S> {
+    fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn to_index(&self, n: NodeIx<Ix>) -> usize {
+        n.index()
+    }
+
+    fn from_index(&self, i: usize) -> NodeIx<Ix> {
+        NodeIx::new(i)
+    }
+}
+
+impl<N, E, Ix: IndexType, S> Visitable for MatrixGraph<N, E, Ix, S> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Vec<bool> {
+        vec![false; self.nodes.len()]
+    }
+
+    fn reset_map(&self, map: &mut Vec<bool>) {
+        map.clear();
+        map.resize(self.nodes.len(), false);
+    }
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> IntoNeighbors for &'a MatrixGraph<N, E, Ix, S> {
+    type Neighbors = MatrixNeighbors<'a, N, E, Ix, S>;
+
+    fn neighbors(self, n: NodeIx<Ix>) -> Self::Neighbors {
+        MatrixGraph::neighbors(self, n)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> IntoNeighborsDirected for &'a MatrixGraph<N, E, Ix, S> {
+    type NeighborsDirected = MatrixNeighbors<'a, N, E, Ix, S>;
+
+    fn neighbors_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::NeighborsDirected {
+        MatrixGraph::neighbors_directed(self, n, dir)
+    }
+}
+
+impl<N, E, Ix: IndexType, S> IntoNodeIdentifiers for &MatrixGraph<N, E, Ix, S> {
+    type NodeIdentifiers = NodeIndices<Ix>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        (0..self.nodes.len()).map(NodeIx::new)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> IntoEdgeReferences for &'a MatrixGraph<N, E, Ix, S> {
+    type EdgeRef = MatrixEdgeRef<'a, E, Ix>;
+    type EdgeReferences = MatrixEdgeReferences<'a, N, E, Ix, S>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        MatrixGraph::edge_references(self)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> IntoEdges for &'a MatrixGraph<N, E, Ix, S> {
+    type Edges = MatrixEdges<'a, N, E, Ix, S>;
+
+    fn edges(self, n: NodeIx<Ix>) -> Self::Edges {
+        MatrixGraph::edges_directed(self, n, Direction::Outgoing)
+    }
+}
+
+impl<'a, N, E, Ix: IndexType, S: CellStore<E>> IntoEdgesDirected for &'a MatrixGraph<N, E, Ix, S> {
+    type EdgesDirected = MatrixEdges<'a, N, E, Ix, S>;
+
+    fn edges_directed(self, n: NodeIx<Ix>, dir: Direction) -> Self::EdgesDirected {
+        MatrixGraph::edges_directed(self, n, dir)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::algo::dijkstra;
+
+    #[test]
+    fn cell_layout_round_trips() {
+        for directed in [true, false] {
+            let n = 9;
+            let mut seen = vec![false; cell_count(directed, n)];
+            for a in 0..n {
+                for b in 0..n {
+                    let i = cell(directed, a, b);
+                    seen[i] = true;
+                    let (x, y) = endpoints(directed, i);
+                    if directed {
+                        assert_eq!((x, y), (a, b));
+                    } else {
+                        assert_eq!((x, y), (a.max(b), a.min(b)));
+                    }
+                }
+            }
+            assert!(seen.iter().all(|&s| s));
+        }
+    }
+
+    #[test]
+    fn matrix_add_remove() {
+        let mut g = MatrixGraph::<&str, u32>::new_directed();
+        let a = g.add("a");
+        let b = g.add("b");
+        let c = g.add("c");
+        let ab = g.add_edge(a, b, 1);
+        g.add_edge(b, c, 2);
+        g.add_edge(c, a, 3);
+        g.add_edge(c, c, 4);
+        assert_eq!(g.add_edge(a, b, 5), ab);
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!(g.edge_weight(ab), Some(&5));
+        assert!(g.contains_edge(c, a));
+        assert!(!g.contains_edge(a, c));
+        assert_eq!(g.neighbors_directed(a, Direction::Incoming).collect::<Vec<_>>(), vec![c]);
+
+        assert_eq!(g.remove(a), Some("a"));
+        assert_eq!(g.edge_count(), 2);
+        // `c` now has index 0
+        assert_eq!(g.weight(a), Some(&"c"));
+        assert!(g.contains_edge(b, a));
+        assert!(g.contains_edge(a, a));
+        assert_eq!(g.edge_weight(g.get_edge(a, a).unwrap()), Some(&4));
+        assert_eq!(g.neighbors(a).collect::<Vec<_>>(), vec![a]);
+    }
+
+    #[test]
+    fn bit_matrix_undirected() {
+        let mut g = BitMatrixGraph::<u8, u8>::new_undirected();
+        let n = (0..10).map(|i| g.add(i)).collect::<Vec<_>>();
+        for i in 0..9 {
+            g.add_edge(n[i + 1], n[i], ());
+        }
+        assert!(g.contains_edge(n[3], n[4]) && g.contains_edge(n[4], n[3]));
+        assert_eq!(g.neighbors(n[4]).collect::<Vec<_>>(), vec![n[3], n[5]]);
+        let e = g.get_edge(n[4], n[5]).unwrap();
+        assert_eq!(g.edge_endpoints(e), Some((n[5], n[4])));
+        assert_eq!(g.remove_edge(e), Some(()));
+        assert_eq!(g.edge_count(), 8);
+        assert!(g.try_add(10).is_ok());
+        let mut big = BitMatrixGraph::<(), u8>::new_directed();
+        while big.try_add(()).is_ok() {}
+        assert_eq!(big.node_count(), 15);
+    }
+
+    #[test]
+    fn graph_conversion() {
+        let mut g = Graph::<u32, u32>::new_directed();
+        let n = (0..4).map(|i| g.add(i)).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], 4);
+        g.add_edge(n[1], n[2], 1);
+        g.add_edge(n[0], n[2], 7);
+        g.add_edge(n[2], n[3], 1);
+        let m = MatrixGraph::<u32, u32>::try_from(&g).unwrap();
+        assert_eq!((m.node_count(), m.edge_count()), (4, 4));
+        let paths = dijkstra(&m, n[0], None, |w| *w);
+        assert_eq!(paths.distance[&n[3]], 6);
+
+        let back = Graph::from(&m);
+        assert_eq!(back.edge_count(), 4);
+        assert_eq!(back.edge_weight(back.get_edge(n[0], n[2]).unwrap()), Some(&7));
+
+        let mut u = Graph::<u32, ()>::new_undirected();
+        let a = u.add(0);
+        let b = u.add(1);
+        u.add_edge(a, b, ());
+        u.add_edge(b, a, ());
+        let bits = BitMatrixGraph::<u32>::try_from(&u).unwrap();
+        assert_eq!(bits.edge_count(), 1);
+        assert_eq!(Graph::from(&bits).edge_count(), 1);
+
+        // 16 directed nodes need 256 cells, one more than `u8` addresses
+        let mut small = Graph::<(), (), u8>::new_directed();
+        (0..16).for_each(|_| { small.add(()); });
+        let refused = MatrixGraph::<(), (), u8>::try_from(&small);
+        assert!(matches!(refused, Err(RecolError::IndexOverflow)));
+    }
+}
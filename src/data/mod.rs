@@ -6,6 +6,28 @@
 pub mod graph;
 #[cfg(feature = "chain")]
 pub mod chain;
+#[cfg(feature = "graph")]
+pub mod csr;
+#[cfg(feature = "graph")]
+pub mod generators;
+#[cfg(feature = "graph")]
+pub mod graph_map;
+#[cfg(feature = "graph")]
+pub mod matrix;
+#[cfg(feature = "graph")]
+pub mod union_find;
 
 #[cfg(feature = "graph")]
-pub use graph::{Graph, Direction, Node, Edge};
+pub use graph::{
+    Graph, StableGraph, Subgraph, Direction, Node, Edge, EdgeRef,
+    ParallelEdges, NodeMap, EdgeMap,
+    NodeIx, EdgeIx, NodeIndex, EdgeIndex, IndexType,
+};
+#[cfg(feature = "graph")]
+pub use csr::Csr;
+#[cfg(feature = "graph")]
+pub use graph_map::GraphMap;
+#[cfg(feature = "graph")]
+pub use matrix::{BitMatrixGraph, MatrixGraph};
+#[cfg(feature = "graph")]
+pub use union_find::UnionFind;
@@ -0,0 +1,102 @@
+//! A disjoint set forest over the integers `0..n`, with path
+//! compression and union by rank.
+//!
+use crate::data::graph::IndexType;
+
+#[derive(Debug, Clone)]
+pub struct UnionFind<K> {
+    parent: Vec<K>,
+    /// An upper bound on the height of each root's tree
+    rank: Vec<u8>,
+}
+
+impl<K: IndexType> UnionFind<K> {
+
+    /// `n` singleton sets, `0` through `n - 1`
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).map(K::new).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    pub fn len(&self) -> usize { self.parent.len() }
+
+    pub fn is_empty(&self) -> bool { self.parent.is_empty() }
+
+    /// The representative of the set containing `x`. Panics if `x`
+    /// is out of bounds.
+    pub fn find(&self, x: K) -> K {
+        let mut x = x;
+        loop {
+            let parent = self.parent[x.index()];
+            if parent == x {
+                return x;
+            }
+            x = parent;
+        }
+    }
+
+    /// Like `find`, but also points every node on the way directly
+    /// at the representative, speeding up later lookups
+    pub fn find_mut(&mut self, x: K) -> K {
+        let root = self.find(x);
+        let mut x = x;
+        while x != root {
+            x = std::mem::replace(&mut self.parent[x.index()], root);
+        }
+        root
+    }
+
+    /// Whether `x` and `y` are in the same set
+    pub fn equiv(&self, x: K, y: K) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    /// Merge the sets containing `x` and `y`. Returns `false` if they
+    /// were already the same set.
+    pub fn union(&mut self, x: K, y: K) -> bool {
+        let (x, y) = (self.find_mut(x), self.find_mut(y));
+        if x == y {
+            return false;
+        }
+        let (rx, ry) = (self.rank[x.index()], self.rank[y.index()]);
+        if rx < ry {
+            self.parent[x.index()] = y;
+        } else {
+            self.parent[y.index()] = x;
+            if rx == ry {
+                self.rank[x.index()] += 1;
+            }
+        }
+        true
+    }
+
+    /// The representative of every element, in order
+    pub fn into_labeling(mut self) -> Vec<K> {
+        for x in 0..self.len() {
+            self.find_mut(K::new(x));
+        }
+        self.parent
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn union_and_find() {
+        let mut sets = UnionFind::<u32>::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.equiv(0, 3));
+        assert!(!sets.equiv(0, 4));
+        let labels = sets.into_labeling();
+        assert!(labels[..4].iter().all(|&l| l == labels[0]));
+        assert_eq!(&labels[4..], &[4, 5]);
+    }
+}
@@ -2,6 +2,8 @@
 //! enum members.
 //!
 use std::{fmt, error, io};
+#[cfg(feature = "graph")]
+use crate::data::graph::{EdgeIx, IndexType, NodeIx};
 
 pub type RecolResult<T> = Result<T, RecolError>;
 
@@ -9,13 +11,51 @@ pub type RecolResult<T> = Result<T, RecolError>;
 pub enum RecolError {
     Io(io::Error),
     General(String),
+    /// No node at the index, or it was removed
+    #[cfg(feature = "graph")]
+    NodeNotFound(NodeIx),
+    /// No edge at the index, or it was removed
+    #[cfg(feature = "graph")]
+    EdgeNotFound(EdgeIx),
+    /// A graph's index type can't address another node or edge
+    IndexOverflow,
+    /// An edge between the nodes exists and the graph forbids
+    /// parallel edges
+    #[cfg(feature = "graph")]
+    ParallelEdge(NodeIx, NodeIx),
+    /// The graph forbids edges from a node to itself
+    #[cfg(feature = "graph")]
+    SelfLoop(NodeIx),
+    /// A `NodeMap` or `EdgeMap` was made before the graph's indices
+    /// last changed
+    #[cfg(feature = "graph")]
+    StaleMap,
+    /// An edge would close a cycle, through the node, in a graph kept
+    /// acyclic
+    #[cfg(feature = "graph")]
+    Cycle(NodeIx),
+    /// An algorithm which needs a directed graph was given an
+    /// undirected one
+    Undirected,
+}
+
+#[cfg(feature = "graph")]
+impl RecolError {
+
+    pub(crate) fn node_not_found<Ix: IndexType>(n: NodeIx<Ix>) -> Self {
+        Self::NodeNotFound(NodeIx::new(n.index()))
+    }
+
+    pub(crate) fn edge_not_found<Ix: IndexType>(e: EdgeIx<Ix>) -> Self {
+        Self::EdgeNotFound(EdgeIx::new(e.index()))
+    }
 }
 
 impl error::Error for RecolError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::Io(e) => e.source(),
-            Self::General(_) => None,
+            _ => None,
         }
     }
 }
@@ -24,7 +64,21 @@ impl fmt::Display for RecolError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(e) => e.fmt(f),
-            Self::General(e) => f.write_str(&e)
+            Self::General(e) => f.write_str(e),
+            #[cfg(feature = "graph")]
+            Self::NodeNotFound(n) => write!(f, "node {} not found", n),
+            #[cfg(feature = "graph")]
+            Self::EdgeNotFound(e) => write!(f, "edge {} not found", e),
+            Self::IndexOverflow => f.write_str("graph index overflow"),
+            #[cfg(feature = "graph")]
+            Self::ParallelEdge(a, b) => write!(f, "an edge from {} to {} already exists", a, b),
+            #[cfg(feature = "graph")]
+            Self::SelfLoop(n) => write!(f, "self loop at {} not allowed", n),
+            #[cfg(feature = "graph")]
+            Self::StaleMap => f.write_str("map is stale: the graph's indices have changed"),
+            #[cfg(feature = "graph")]
+            Self::Cycle(n) => write!(f, "graph contains a cycle through node {}", n),
+            Self::Undirected => f.write_str("expected a directed graph"),
         }
     }
 }
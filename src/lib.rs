@@ -8,18 +8,27 @@
 //! Currently, only the graph data structure is (kind of)
 //! somewhat implemented. Very much so a work in progress!
 //! ```rust
-//! use recollection::data::{Graph, Node, Edge};
+//! use recollection::{data::{Graph, NodeIx}, RecolError, RecolResult};
+//! # fn main() -> RecolResult<()> {
 //! let mut g = Graph::<String, usize>::new_directed();
-//! let n1: usize = g.add("First node!")?;
-//! let n2: usize = g.add("Second node!")?;
-//! let e1: Edge<usize> = g.add_edge(n1, n2, 10)?;
+//! let n1 = g.try_add("First node!".into())?;
+//! let n2 = g.try_add("Second node!".into())?;
+//! let _e1 = g.try_add_edge(n1, n2, 10)?;
 //! assert_eq!(g.node_count(), 2);
 //! assert_eq!(g.edge_count(), 1);
-//!
-//! ````
+//! // A bad index is an error rather than a panic
+//! let missing = NodeIx::new(7);
+//! assert!(matches!(g.try_add_edge(n1, missing, 20), Err(RecolError::NodeNotFound(n)) if n == missing));
+//! # Ok(())
+//! # }
+//! ```
 pub mod data;
+#[cfg(feature = "graph")]
+pub mod algo;
 pub mod error;
 pub mod prelude;
+#[cfg(feature = "graph")]
+pub mod visit;
 #[cfg(feature = "fs")]
 pub mod fs;
 #[cfg(feature = "config")]
@@ -28,6 +37,7 @@ pub mod config;
 pub mod channel;
 
 #[cfg(feature = "derive")]
+#[allow(unused_imports)]
 pub use recollection_derive::*;
 pub use error::{RecolError, RecolResult};
 
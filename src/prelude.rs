@@ -2,5 +2,13 @@
 //!
 pub use crate::{
     error::{RecolError, RecolResult},
-    data::graph::{Graph, Direction, Node, Edge},
+    data::graph::{
+        Graph, StableGraph, Subgraph, Direction, Node, Edge, EdgeRef,
+        ParallelEdges, NodeMap, EdgeMap,
+        NodeIx, EdgeIx, NodeIndex, EdgeIndex, IndexType,
+    },
+    data::csr::Csr,
+    data::graph_map::GraphMap,
+    data::matrix::{BitMatrixGraph, MatrixGraph},
+    data::union_find::UnionFind,
 };
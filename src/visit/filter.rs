@@ -0,0 +1,200 @@
+//! A view of a graph with some nodes hidden.
+//!
+use crate::data::graph::Direction;
+use super::{
+    Data, EdgeReference, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoEdgesDirected,
+    IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount, NodeIndexable, Visitable,
+};
+
+/// Hides every node for which the predicate returns `false`, along
+/// with the edges touching them. Nothing is copied: the predicate is
+/// evaluated as the graph is walked. The walk traits are implemented
+/// for `&NodeFiltered`.
+///
+/// ```
+/// use recollection::{data::Graph, visit::{Bfs, NodeFiltered}};
+///
+/// let mut g = Graph::<u32, ()>::new_directed();
+/// let a = g.add(1);
+/// let b = g.add(2);
+/// let c = g.add(3);
+/// g.add_edge(a, b, ());
+/// g.add_edge(b, c, ());
+/// let odd = NodeFiltered(&g, |n| g.weight(n).unwrap() % 2 == 1);
+/// let mut bfs = Bfs::new(&odd, a);
+/// assert_eq!(bfs.next(&odd), Some(a));
+/// assert_eq!(bfs.next(&odd), None);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NodeFiltered<G, F>(pub G, pub F);
+
+impl<G, F> NodeFiltered<G, F>
+where
+    G: GraphBase,
+    F: Fn(G::NodeId) -> bool,
+{
+    pub fn includes(&self, n: G::NodeId) -> bool {
+        (self.1)(n)
+    }
+}
+
+/// The visible nodes among those yielded by `iter`, if any
+#[derive(Debug, Clone)]
+pub struct NodeFilteredNodes<'a, I, F> {
+    iter: Option<I>,
+    filter: &'a F,
+}
+
+impl<'a, I, F> Iterator for NodeFilteredNodes<'a, I, F>
+where
+    I: Iterator,
+    I::Item: Copy,
+    F: Fn(I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let filter = self.filter;
+        self.iter.as_mut()?.find(|&n| filter(n))
+    }
+}
+
+/// The edges among those yielded by `iter`, if any, with both ends
+/// visible
+#[derive(Debug, Clone)]
+pub struct NodeFilteredEdges<'a, I, F> {
+    iter: Option<I>,
+    filter: &'a F,
+}
+
+impl<'a, I, F> Iterator for NodeFilteredEdges<'a, I, F>
+where
+    I: Iterator,
+    I::Item: EdgeReference,
+    F: Fn(<I::Item as EdgeReference>::NodeId) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let filter = self.filter;
+        self.iter.as_mut()?.find(|e| filter(e.source()) && filter(e.target()))
+    }
+}
+
+impl<G: GraphBase, F> GraphBase for NodeFiltered<G, F> {
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+}
+
+impl<G: Data, F> Data for NodeFiltered<G, F> {
+    type NodeWeight = G::NodeWeight;
+    type EdgeWeight = G::EdgeWeight;
+}
+
+impl<G: GraphProp, F> GraphProp for NodeFiltered<G, F> {
+    fn is_directed(&self) -> bool { self.0.is_directed() }
+}
+
+/// Counts the visible nodes, so takes time linear in the graph
+impl<G, F> NodeCount for NodeFiltered<G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(G::NodeId) -> bool,
+{
+    fn node_count(&self) -> usize {
+        self.0.node_identifiers().filter(|&n| (self.1)(n)).count()
+    }
+}
+
+impl<G: NodeIndexable, F> NodeIndexable for NodeFiltered<G, F> {
+    fn node_bound(&self) -> usize { self.0.node_bound() }
+
+    fn to_index(&self, n: G::NodeId) -> usize { self.0.to_index(n) }
+
+    fn from_index(&self, i: usize) -> G::NodeId { self.0.from_index(i) }
+}
+
+impl<G: Visitable, F> Visitable for NodeFiltered<G, F> {
+    type Map = G::Map;
+
+    fn visit_map(&self) -> G::Map { self.0.visit_map() }
+
+    fn reset_map(&self, map: &mut G::Map) { self.0.reset_map(map) }
+}
+
+impl<'a, G, F> IntoNeighbors for &'a NodeFiltered<G, F>
+where
+    G: IntoNeighbors,
+    F: Fn(G::NodeId) -> bool,
+{
+    type Neighbors = NodeFilteredNodes<'a, G::Neighbors, F>;
+
+    fn neighbors(self, n: G::NodeId) -> Self::Neighbors {
+        let iter = (self.1)(n).then(|| self.0.neighbors(n));
+        NodeFilteredNodes { iter, filter: &self.1 }
+    }
+}
+
+impl<'a, G, F> IntoNeighborsDirected for &'a NodeFiltered<G, F>
+where
+    G: IntoNeighborsDirected,
+    F: Fn(G::NodeId) -> bool,
+{
+    type NeighborsDirected = NodeFilteredNodes<'a, G::NeighborsDirected, F>;
+
+    fn neighbors_directed(self, n: G::NodeId, dir: Direction) -> Self::NeighborsDirected {
+        let iter = (self.1)(n).then(|| self.0.neighbors_directed(n, dir));
+        NodeFilteredNodes { iter, filter: &self.1 }
+    }
+}
+
+impl<'a, G, F> IntoNodeIdentifiers for &'a NodeFiltered<G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(G::NodeId) -> bool,
+{
+    type NodeIdentifiers = NodeFilteredNodes<'a, G::NodeIdentifiers, F>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        NodeFilteredNodes { iter: Some(self.0.node_identifiers()), filter: &self.1 }
+    }
+}
+
+impl<'a, G, F> IntoEdgeReferences for &'a NodeFiltered<G, F>
+where
+    G: IntoEdgeReferences,
+    F: Fn(G::NodeId) -> bool,
+{
+    type EdgeRef = G::EdgeRef;
+    type EdgeReferences = NodeFilteredEdges<'a, G::EdgeReferences, F>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        NodeFilteredEdges { iter: Some(self.0.edge_references()), filter: &self.1 }
+    }
+}
+
+impl<'a, G, F> IntoEdges for &'a NodeFiltered<G, F>
+where
+    G: IntoEdges,
+    F: Fn(G::NodeId) -> bool,
+{
+    type Edges = NodeFilteredEdges<'a, G::Edges, F>;
+
+    fn edges(self, n: G::NodeId) -> Self::Edges {
+        let iter = (self.1)(n).then(|| self.0.edges(n));
+        NodeFilteredEdges { iter, filter: &self.1 }
+    }
+}
+
+impl<'a, G, F> IntoEdgesDirected for &'a NodeFiltered<G, F>
+where
+    G: IntoEdgesDirected,
+    F: Fn(G::NodeId) -> bool,
+{
+    type EdgesDirected = NodeFilteredEdges<'a, G::EdgesDirected, F>;
+
+    fn edges_directed(self, n: G::NodeId, dir: Direction) -> Self::EdgesDirected {
+        let iter = (self.1)(n).then(|| self.0.edges_directed(n, dir));
+        NodeFilteredEdges { iter, filter: &self.1 }
+    }
+}
@@ -0,0 +1,18 @@
+//! Graph traversal. Walkers which step through a graph without
+//! holding on to it, so it can be mutated between steps, and the
+//! traits which let them (and the algorithms in `algo`) work on any
+//! graph type or adapter.
+//!
+pub mod filter;
+pub mod reversed;
+pub mod traits;
+pub mod traversal;
+
+pub use filter::NodeFiltered;
+pub use reversed::Reversed;
+pub use traits::{
+    Data, EdgeReference, GraphBase, GraphProp, GraphRef, IntoEdgeReferences, IntoEdges,
+    IntoEdgesDirected, IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount,
+    NodeIndexable, VisitMap, Visitable,
+};
+pub use traversal::{Bfs, Dfs, DfsEvent, DfsEvents, Time};
@@ -0,0 +1,146 @@
+//! A view of a graph with every edge reversed.
+//!
+use crate::data::graph::Direction;
+use super::{
+    Data, EdgeReference, GraphBase, GraphProp, GraphRef, IntoEdgeReferences, IntoEdges,
+    IntoEdgesDirected, IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount,
+    NodeIndexable, Visitable,
+};
+
+/// Wraps a graph handle so edges are walked against their direction.
+/// Nothing is copied; `Reversed(&g)` is as cheap as `&g`.
+///
+/// ```
+/// use recollection::{data::Graph, visit::{Dfs, Reversed}};
+///
+/// let mut g = Graph::<(), ()>::new_directed();
+/// let a = g.add(());
+/// let b = g.add(());
+/// g.add_edge(a, b, ());
+/// let mut dfs = Dfs::new(Reversed(&g), b);
+/// assert_eq!(dfs.next(Reversed(&g)), Some(b));
+/// assert_eq!(dfs.next(Reversed(&g)), Some(a));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Reversed<G>(pub G);
+
+/// An edge of a `Reversed` graph, with its endpoints swapped
+#[derive(Debug, Clone, Copy)]
+pub struct ReversedEdgeRef<R>(R);
+
+impl<R: EdgeReference> EdgeReference for ReversedEdgeRef<R> {
+    type NodeId = R::NodeId;
+    type EdgeId = R::EdgeId;
+    type Weight = R::Weight;
+
+    fn source(&self) -> R::NodeId { self.0.target() }
+
+    fn target(&self) -> R::NodeId { self.0.source() }
+
+    fn weight(&self) -> &R::Weight { self.0.weight() }
+
+    fn id(&self) -> R::EdgeId { self.0.id() }
+}
+
+/// The edges of a `Reversed` graph
+#[derive(Debug, Clone)]
+pub struct ReversedEdges<I>(I);
+
+impl<I> Iterator for ReversedEdges<I>
+where
+    I: Iterator,
+    I::Item: EdgeReference,
+{
+    type Item = ReversedEdgeRef<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(ReversedEdgeRef)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<G: GraphBase> GraphBase for Reversed<G> {
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+}
+
+impl<G: GraphRef> GraphRef for Reversed<G> {}
+
+impl<G: Data> Data for Reversed<G> {
+    type NodeWeight = G::NodeWeight;
+    type EdgeWeight = G::EdgeWeight;
+}
+
+impl<G: GraphProp> GraphProp for Reversed<G> {
+    fn is_directed(&self) -> bool { self.0.is_directed() }
+}
+
+impl<G: NodeCount> NodeCount for Reversed<G> {
+    fn node_count(&self) -> usize { self.0.node_count() }
+}
+
+impl<G: NodeIndexable> NodeIndexable for Reversed<G> {
+    fn node_bound(&self) -> usize { self.0.node_bound() }
+
+    fn to_index(&self, n: G::NodeId) -> usize { self.0.to_index(n) }
+
+    fn from_index(&self, i: usize) -> G::NodeId { self.0.from_index(i) }
+}
+
+impl<G: Visitable> Visitable for Reversed<G> {
+    type Map = G::Map;
+
+    fn visit_map(&self) -> G::Map { self.0.visit_map() }
+
+    fn reset_map(&self, map: &mut G::Map) { self.0.reset_map(map) }
+}
+
+impl<G: IntoNeighborsDirected> IntoNeighbors for Reversed<G> {
+    type Neighbors = G::NeighborsDirected;
+
+    fn neighbors(self, n: G::NodeId) -> Self::Neighbors {
+        self.0.neighbors_directed(n, Direction::Incoming)
+    }
+}
+
+impl<G: IntoNeighborsDirected> IntoNeighborsDirected for Reversed<G> {
+    type NeighborsDirected = G::NeighborsDirected;
+
+    fn neighbors_directed(self, n: G::NodeId, dir: Direction) -> Self::NeighborsDirected {
+        self.0.neighbors_directed(n, dir.other())
+    }
+}
+
+impl<G: IntoNodeIdentifiers> IntoNodeIdentifiers for Reversed<G> {
+    type NodeIdentifiers = G::NodeIdentifiers;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers { self.0.node_identifiers() }
+}
+
+impl<G: IntoEdgeReferences> IntoEdgeReferences for Reversed<G> {
+    type EdgeRef = ReversedEdgeRef<G::EdgeRef>;
+    type EdgeReferences = ReversedEdges<G::EdgeReferences>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        ReversedEdges(self.0.edge_references())
+    }
+}
+
+impl<G: IntoEdgesDirected> IntoEdges for Reversed<G> {
+    type Edges = ReversedEdges<G::EdgesDirected>;
+
+    fn edges(self, n: G::NodeId) -> Self::Edges {
+        ReversedEdges(self.0.edges_directed(n, Direction::Incoming))
+    }
+}
+
+impl<G: IntoEdgesDirected> IntoEdgesDirected for Reversed<G> {
+    type EdgesDirected = ReversedEdges<G::EdgesDirected>;
+
+    fn edges_directed(self, n: G::NodeId, dir: Direction) -> Self::EdgesDirected {
+        ReversedEdges(self.0.edges_directed(n, dir.other()))
+    }
+}
@@ -0,0 +1,184 @@
+//! Traits describing what a graph can do, so walkers and algorithms
+//! can be written once for every graph type and adapter.
+//!
+//! Most are implemented for references (`&Graph`), since they hand
+//! out iterators borrowing the graph; `GraphRef` marks such cheap,
+//! copyable handles.
+//!
+use std::{collections::HashSet, fmt, hash::{BuildHasher, Hash}};
+use crate::data::graph::{Direction, IndexType, NodeIx};
+
+/// The index types of a graph
+pub trait GraphBase {
+    type NodeId: Copy + Eq + Hash + Ord + fmt::Debug;
+    type EdgeId: Copy + Eq + Hash + fmt::Debug;
+}
+
+/// A copyable handle to a graph, usually a reference
+pub trait GraphRef: Copy + GraphBase {}
+
+/// The weight types of a graph
+pub trait Data: GraphBase {
+    type NodeWeight;
+    type EdgeWeight;
+}
+
+/// Whether edges have a direction
+pub trait GraphProp: GraphBase {
+    fn is_directed(&self) -> bool;
+}
+
+pub trait NodeCount: GraphBase {
+    fn node_count(&self) -> usize;
+}
+
+/// A graph whose nodes map to the integers `0..node_bound()`, so
+/// per node data can be kept in a `Vec`
+pub trait NodeIndexable: GraphBase {
+    /// One past the largest index of any node
+    fn node_bound(&self) -> usize;
+
+    fn to_index(&self, n: Self::NodeId) -> usize;
+
+    #[allow(clippy::wrong_self_convention)]
+    fn from_index(&self, i: usize) -> Self::NodeId;
+}
+
+/// The nodes reachable from a node over one edge: its successors
+/// if directed, all neighbors if not
+pub trait IntoNeighbors: GraphRef {
+    type Neighbors: Iterator<Item = Self::NodeId>;
+
+    fn neighbors(self, n: Self::NodeId) -> Self::Neighbors;
+}
+
+/// Neighbors across edges in a given direction. For undirected
+/// graphs the direction is ignored.
+pub trait IntoNeighborsDirected: IntoNeighbors {
+    type NeighborsDirected: Iterator<Item = Self::NodeId>;
+
+    fn neighbors_directed(self, n: Self::NodeId, dir: Direction) -> Self::NeighborsDirected;
+}
+
+pub trait IntoNodeIdentifiers: GraphRef {
+    type NodeIdentifiers: Iterator<Item = Self::NodeId>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers;
+}
+
+/// A borrowed edge
+pub trait EdgeReference: Copy {
+    type NodeId;
+    type EdgeId;
+    type Weight;
+
+    fn source(&self) -> Self::NodeId;
+
+    fn target(&self) -> Self::NodeId;
+
+    fn weight(&self) -> &Self::Weight;
+
+    fn id(&self) -> Self::EdgeId;
+}
+
+/// Every edge of the graph
+pub trait IntoEdgeReferences: Data + GraphRef {
+    type EdgeRef: EdgeReference<NodeId = Self::NodeId, EdgeId = Self::EdgeId, Weight = Self::EdgeWeight>;
+    type EdgeReferences: Iterator<Item = Self::EdgeRef>;
+
+    fn edge_references(self) -> Self::EdgeReferences;
+}
+
+/// The edges leaving a node, or at a node if undirected. Undirected
+/// edges are oriented so the node is their source.
+pub trait IntoEdges: IntoEdgeReferences + IntoNeighbors {
+    type Edges: Iterator<Item = Self::EdgeRef>;
+
+    fn edges(self, n: Self::NodeId) -> Self::Edges;
+}
+
+/// The edges at a node in a given direction: leaving it if
+/// `Outgoing`, entering it if `Incoming`
+pub trait IntoEdgesDirected: IntoEdges + IntoNeighborsDirected {
+    type EdgesDirected: Iterator<Item = Self::EdgeRef>;
+
+    fn edges_directed(self, n: Self::NodeId, dir: Direction) -> Self::EdgesDirected;
+}
+
+/// A set of visited nodes
+pub trait VisitMap<N> {
+    /// Mark `n` as visited. Returns `false` if it already was.
+    fn visit(&mut self, n: N) -> bool;
+
+    fn is_visited(&self, n: &N) -> bool;
+}
+
+/// A graph which can create a `VisitMap` for its nodes
+pub trait Visitable: GraphBase {
+    type Map: VisitMap<Self::NodeId>;
+
+    fn visit_map(&self) -> Self::Map;
+
+    /// Clear `map` and size it for the graph as it is now
+    fn reset_map(&self, map: &mut Self::Map);
+}
+
+/// Indexed by node, growing if the graph has grown since the map
+/// was created
+impl<Ix: IndexType> VisitMap<NodeIx<Ix>> for Vec<bool> {
+    fn visit(&mut self, n: NodeIx<Ix>) -> bool {
+        if self.len() <= n.index() {
+            self.resize(n.index() + 1, false);
+        }
+        !std::mem::replace(&mut self[n.index()], true)
+    }
+
+    fn is_visited(&self, n: &NodeIx<Ix>) -> bool {
+        self.get(n.index()).copied().unwrap_or(false)
+    }
+}
+
+impl<N: Eq + Hash, S: BuildHasher> VisitMap<N> for HashSet<N, S> {
+    fn visit(&mut self, n: N) -> bool {
+        self.insert(n)
+    }
+
+    fn is_visited(&self, n: &N) -> bool {
+        self.contains(n)
+    }
+}
+
+macro_rules! forward_ref {
+    ($($t:ident { $($body:tt)* })*) => {$(
+        impl<'a, G: $t> $t for &'a G { $($body)* }
+    )*}
+}
+
+forward_ref! {
+    GraphBase {
+        type NodeId = G::NodeId;
+        type EdgeId = G::EdgeId;
+    }
+    Data {
+        type NodeWeight = G::NodeWeight;
+        type EdgeWeight = G::EdgeWeight;
+    }
+    GraphProp {
+        fn is_directed(&self) -> bool { (**self).is_directed() }
+    }
+    NodeCount {
+        fn node_count(&self) -> usize { (**self).node_count() }
+    }
+    NodeIndexable {
+        fn node_bound(&self) -> usize { (**self).node_bound() }
+        fn to_index(&self, n: G::NodeId) -> usize { (**self).to_index(n) }
+        fn from_index(&self, i: usize) -> G::NodeId { (**self).from_index(i) }
+    }
+    Visitable {
+        type Map = G::Map;
+        fn visit_map(&self) -> G::Map { (**self).visit_map() }
+        fn reset_map(&self, map: &mut G::Map) { (**self).reset_map(map) }
+    }
+}
+
+impl<G: GraphBase> GraphRef for &G {}
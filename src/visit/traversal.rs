@@ -0,0 +1,322 @@
+//! Breadth and depth first walkers. Like `WalkNeighbors`, none of
+//! them hold a borrow of the graph between steps: each step takes the
+//! graph as an argument, so node weights may be mutated mid-walk.
+//!
+//! `Bfs` and `Dfs` walk any graph implementing the `visit` traits,
+//! along edges in either direction.
+//! `DfsEvents` resumes each node's edge list with `WalkNeighbors`,
+//! so is specific to `Graph`.
+//!
+use std::collections::VecDeque;
+use crate::data::graph::{DefaultIx, Direction, Graph, IndexType, NodeIx, WalkNeighbors};
+use super::{GraphRef, IntoNeighborsDirected, VisitMap, Visitable};
+
+/// A breadth first walk, yielding each reachable node once
+#[derive(Debug, Clone)]
+pub struct Bfs<N = NodeIx, VM = Vec<bool>> {
+    pub queue: VecDeque<N>,
+    pub discovered: VM,
+    dir: Direction,
+}
+
+impl<N: Copy, VM: VisitMap<N>> Bfs<N, VM> {
+
+    /// Walk outgoing edges starting at `start`
+    pub fn new<G>(g: G, start: N) -> Self
+    where
+        G: GraphRef + Visitable<NodeId = N, Map = VM>,
+    {
+        Self::with_direction(g, start, Direction::Outgoing)
+    }
+
+    /// Walk edges in direction `dir` starting at `start`. On an
+    /// undirected graph the direction is ignored.
+    pub fn with_direction<G>(g: G, start: N, dir: Direction) -> Self
+    where
+        G: GraphRef + Visitable<NodeId = N, Map = VM>,
+    {
+        let mut bfs = Self {
+            queue: VecDeque::new(),
+            discovered: g.visit_map(),
+            dir,
+        };
+        bfs.move_to(start);
+        bfs
+    }
+
+    /// Restart the walk from `start`. Nodes discovered by earlier
+    /// walks are not visited again until `reset` is called.
+    pub fn move_to(&mut self, start: N) {
+        self.queue.clear();
+        if self.discovered.visit(start) {
+            self.queue.push_back(start);
+        }
+    }
+
+    /// Forget all discovered nodes and empty the queue
+    pub fn reset<G>(&mut self, g: G)
+    where
+        G: Visitable<NodeId = N, Map = VM>,
+    {
+        self.queue.clear();
+        g.reset_map(&mut self.discovered);
+    }
+
+    pub fn next<G>(&mut self, g: G) -> Option<N>
+    where
+        G: IntoNeighborsDirected<NodeId = N>,
+    {
+        let node = self.queue.pop_front()?;
+        for succ in g.neighbors_directed(node, self.dir) {
+            if self.discovered.visit(succ) {
+                self.queue.push_back(succ);
+            }
+        }
+        Some(node)
+    }
+}
+
+/// A depth first walk, yielding each reachable node once in preorder
+#[derive(Debug, Clone)]
+pub struct Dfs<N = NodeIx, VM = Vec<bool>> {
+    pub stack: Vec<N>,
+    pub discovered: VM,
+    dir: Direction,
+}
+
+impl<N: Copy, VM: VisitMap<N>> Dfs<N, VM> {
+
+    /// Walk outgoing edges starting at `start`
+    pub fn new<G>(g: G, start: N) -> Self
+    where
+        G: GraphRef + Visitable<NodeId = N, Map = VM>,
+    {
+        Self::with_direction(g, start, Direction::Outgoing)
+    }
+
+    /// Walk edges in direction `dir` starting at `start`. On an
+    /// undirected graph the direction is ignored.
+    pub fn with_direction<G>(g: G, start: N, dir: Direction) -> Self
+    where
+        G: GraphRef + Visitable<NodeId = N, Map = VM>,
+    {
+        let mut dfs = Self {
+            stack: Vec::new(),
+            discovered: g.visit_map(),
+            dir,
+        };
+        dfs.move_to(start);
+        dfs
+    }
+
+    /// Restart the walk from `start`. Nodes discovered by earlier
+    /// walks are not visited again until `reset` is called.
+    pub fn move_to(&mut self, start: N) {
+        self.stack.clear();
+        self.stack.push(start);
+    }
+
+    /// Forget all discovered nodes and empty the stack
+    pub fn reset<G>(&mut self, g: G)
+    where
+        G: Visitable<NodeId = N, Map = VM>,
+    {
+        self.stack.clear();
+        g.reset_map(&mut self.discovered);
+    }
+
+    pub fn next<G>(&mut self, g: G) -> Option<N>
+    where
+        G: IntoNeighborsDirected<NodeId = N>,
+    {
+        while let Some(node) = self.stack.pop() {
+            if !self.discovered.visit(node) {
+                continue;
+            }
+            for succ in g.neighbors_directed(node, self.dir) {
+                if !self.discovered.is_visited(&succ) {
+                    self.stack.push(succ);
+                }
+            }
+            return Some(node);
+        }
+        None
+    }
+}
+
+/// A point in time of a depth first search, counted in discover
+/// and finish events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time(pub usize);
+
+/// The events emitted by `DfsEvents`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfsEvent<Ix = DefaultIx> {
+    /// A node is reached for the first time
+    Discover(NodeIx<Ix>, Time),
+    /// An edge to an undiscovered node, making it a child in the
+    /// search tree
+    TreeEdge(NodeIx<Ix>, NodeIx<Ix>),
+    /// An edge to an ancestor which has not yet finished
+    BackEdge(NodeIx<Ix>, NodeIx<Ix>),
+    /// An edge to an already finished node
+    CrossForwardEdge(NodeIx<Ix>, NodeIx<Ix>),
+    /// All of a node's edges have been explored
+    Finish(NodeIx<Ix>, Time),
+}
+
+/// A depth first walk yielding `DfsEvent`s, for algorithms which
+/// need more than the preorder given by `Dfs`
+#[derive(Debug, Clone)]
+pub struct DfsEvents<Ix: IndexType = DefaultIx> {
+    stack: Vec<(NodeIx<Ix>, WalkNeighbors<Ix>)>,
+    discovered: Vec<bool>,
+    finished: Vec<bool>,
+    pending: Option<DfsEvent<Ix>>,
+    time: usize,
+    dir: Direction,
+}
+
+impl<Ix: IndexType> DfsEvents<Ix> {
+
+    /// Walk outgoing edges starting at `start`
+    pub fn new<N: Clone, E: Clone>(g: &Graph<N, E, Ix>, start: NodeIx<Ix>) -> Self {
+        Self::with_direction(g, start, Direction::Outgoing)
+    }
+
+    /// Walk edges in direction `dir` starting at `start`. On an
+    /// undirected graph the direction is ignored.
+    pub fn with_direction<N: Clone, E: Clone>(
+        g: &Graph<N, E, Ix>,
+        start: NodeIx<Ix>,
+        dir: Direction) -> Self
+    {
+        let mut dfs = Self {
+            stack: Vec::new(),
+            discovered: vec![false; g.node_count()],
+            finished: vec![false; g.node_count()],
+            pending: None,
+            time: 0,
+            dir,
+        };
+        dfs.move_to(g, start);
+        dfs
+    }
+
+    /// Restart the walk from `start`, continuing the clock. Nodes
+    /// discovered by earlier walks are not visited again.
+    pub fn move_to<N: Clone, E: Clone>(&mut self, g: &Graph<N, E, Ix>, start: NodeIx<Ix>) {
+        self.stack.clear();
+        self.pending = None;
+        if self.discovered.visit(start) {
+            self.pending = Some(DfsEvent::Discover(start, self.tick()));
+            self.stack.push((start, g.neighbors_directed(start, self.dir).detach()));
+        }
+    }
+
+    /// Whether node `n` has been reached by any walk so far
+    pub fn is_discovered(&self, n: NodeIx<Ix>) -> bool {
+        self.discovered.is_visited(&n)
+    }
+
+    pub fn next<N: Clone, E: Clone>(&mut self, g: &Graph<N, E, Ix>) -> Option<DfsEvent<Ix>> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        let (node, walk) = self.stack.last_mut()?;
+        let node = *node;
+        match walk.next_node(g) {
+            Some(succ) => {
+                if self.discovered.visit(succ) {
+                    self.pending = Some(DfsEvent::Discover(succ, self.tick()));
+                    self.stack.push((succ, g.neighbors_directed(succ, self.dir).detach()));
+                    Some(DfsEvent::TreeEdge(node, succ))
+                } else if !self.finished.is_visited(&succ) {
+                    Some(DfsEvent::BackEdge(node, succ))
+                } else {
+                    Some(DfsEvent::CrossForwardEdge(node, succ))
+                }
+            }
+            None => {
+                self.stack.pop();
+                self.finished.visit(node);
+                Some(DfsEvent::Finish(node, self.tick()))
+            }
+        }
+    }
+
+    fn tick(&mut self) -> Time {
+        let time = Time(self.time);
+        self.time += 1;
+        time
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::visit::Reversed;
+
+    fn diamond() -> (Graph<usize, ()>, Vec<NodeIx>) {
+        let mut g = Graph::new_directed();
+        let n = (0..4).map(|i| g.add(i)).collect::<Vec<_>>();
+        g.add_edge(n[0], n[1], ());
+        g.add_edge(n[0], n[2], ());
+        g.add_edge(n[1], n[3], ());
+        g.add_edge(n[2], n[3], ());
+        (g, n)
+    }
+
+    #[test]
+    fn bfs_visits_by_level() {
+        let (mut g, n) = diamond();
+        let mut bfs = Bfs::new(&g, n[0]);
+        let mut order = vec![];
+        while let Some(node) = bfs.next(&g) {
+            *g.weight_mut(node).unwrap() += 10;
+            order.push(node);
+        }
+        assert_eq!(order[0], n[0]);
+        assert_eq!(order[3], n[3]);
+        assert_eq!(g.weight(n[3]), Some(&13));
+    }
+
+    #[test]
+    fn dfs_respects_direction() {
+        let (g, n) = diamond();
+        let mut dfs = Dfs::new(Reversed(&g), n[3]);
+        let mut seen = 0;
+        while dfs.next(Reversed(&g)).is_some() { seen += 1; }
+        assert_eq!(seen, 4);
+        let mut dfs = Dfs::with_direction(&g, n[3], Direction::Incoming);
+        let mut seen = 0;
+        while dfs.next(&g).is_some() { seen += 1; }
+        assert_eq!(seen, 4);
+        let mut bfs = Bfs::with_direction(&g, n[1], Direction::Incoming);
+        assert_eq!(bfs.next(&g), Some(n[1]));
+        assert_eq!(bfs.next(&g), Some(n[0]));
+        assert_eq!(bfs.next(&g), None);
+        let mut dfs = Dfs::new(&g, n[3]);
+        assert_eq!(dfs.next(&g), Some(n[3]));
+        assert_eq!(dfs.next(&g), None);
+        dfs.move_to(n[1]);
+        assert_eq!(dfs.next(&g), Some(n[1]));
+        assert_eq!(dfs.next(&g), None);
+    }
+
+    #[test]
+    fn dfs_events_classify_edges() {
+        let (mut g, n) = diamond();
+        g.add_edge(n[3], n[0], ());
+        let mut dfs = DfsEvents::new(&g, n[0]);
+        let mut events = vec![];
+        while let Some(ev) = dfs.next(&g) {
+            events.push(ev);
+        }
+        assert_eq!(events[0], DfsEvent::Discover(n[0], Time(0)));
+        assert!(events.contains(&DfsEvent::BackEdge(n[3], n[0])));
+        assert_eq!(events.iter().filter(|e| matches!(e, DfsEvent::Finish(..))).count(), 4);
+        assert!(matches!(events.last(), Some(DfsEvent::Finish(x, Time(7))) if *x == n[0]));
+    }
+}
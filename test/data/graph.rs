@@ -33,7 +33,7 @@ fn graph_adds_nodes_and_edges() -> RecolResult<()> {
     for i in 0..20 {
         let n = g.add(i);
         for (j, node) in (0..i).enumerate() {
-            let _edge = g.add_edge(n, node, i+j);
+            let _edge = g.add_edge(n, NodeIx::new(node), i+j);
         }
         assert_eq!(g.node_count(), i+1);
         // assert_eq!(g.edge_count(), i*i);
@@ -93,3 +93,281 @@ fn graph_insert_remove() -> RecolResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn graph_remove_repoints_swapped_node() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    let a = g.add(0);
+    let b = g.add(1);
+    let c = g.add(2);
+    g.add_edge(b, c, 0);
+    g.add_edge(c, b, 1);
+    g.remove(a);
+    // `c` was swapped into the slot left by `a`
+    assert_eq!(g.weight(a), Some(&2));
+    assert_eq!(g.neighbors(a).collect::<Vec<_>>(), vec![b]);
+    assert_eq!(g.neighbors(b).collect::<Vec<_>>(), vec![a]);
+    assert!(g.contains_edge(b, a));
+    Ok(())
+}
+
+#[test]
+fn stable_graph_keeps_indices() -> RecolResult<()> {
+    let mut g = StableGraph::<usize, usize>::new_directed();
+    let a = g.add(0);
+    let b = g.add(1);
+    let c = g.add(2);
+    let bc = g.add_edge(b, c, 0);
+    g.remove(a);
+    assert_eq!(g.weight(c), Some(&2));
+    assert_eq!(g.edge_endpoints(bc), Some((b, c)));
+    let g: Graph<usize, usize> = g.into();
+    assert_eq!(g.node_count(), 2);
+    assert_eq!(g.edge_endpoints(EdgeIx::new(0)), Some((NodeIx::new(0), NodeIx::new(1))));
+    Ok(())
+}
+
+#[test]
+fn graph_with_u32_indices() -> RecolResult<()> {
+    let mut g = Graph::<&str, (), u32>::new_undirected();
+    let a: NodeIndex<u32> = g.add("a");
+    let b = g.add("b");
+    let e: EdgeIndex<u32> = g.add_edge(a, b, ());
+    assert_eq!(g.edge_endpoints(e), Some((a, b)));
+    assert_eq!(g.neighbors(b).collect::<Vec<_>>(), vec![a]);
+    assert_eq!(g.node_indices().map(|n| n.index()).collect::<Vec<_>>(), vec![0, 1]);
+    Ok(())
+}
+
+#[test]
+fn graph_try_methods_report_errors() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    let a = g.try_add(0)?;
+    let b = g.try_add(1)?;
+    let ab = g.try_add_edge(a, b, 0)?;
+    let missing = NodeIx::new(5);
+    assert!(matches!(g.try_add_edge(a, missing, 1), Err(RecolError::NodeNotFound(n)) if n == missing));
+    assert!(matches!(g.try_update_edge(missing, a, 1), Err(RecolError::NodeNotFound(_))));
+    assert_eq!(g.try_remove_edge(ab)?, 0);
+    assert!(matches!(g.try_remove_edge(ab), Err(RecolError::EdgeNotFound(e)) if e == ab));
+    assert_eq!(g.try_remove(b)?, 1);
+    assert!(matches!(g.try_remove(b), Err(RecolError::NodeNotFound(_))));
+
+    let mut small = Graph::<(), (), u8>::new_directed();
+    for _ in 0..u8::MAX {
+        small.try_add(())?;
+    }
+    assert!(matches!(small.try_add(()), Err(RecolError::IndexOverflow)));
+
+    let mut sg = StableGraph::<usize, usize>::new_directed();
+    let a = sg.try_add(0)?;
+    sg.try_remove(a)?;
+    assert!(matches!(sg.try_add_edge(a, a, 0), Err(RecolError::NodeNotFound(_))));
+    Ok(())
+}
+
+#[test]
+fn graph_edge_references() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    let a = g.add(0);
+    let b = g.add(1);
+    let c = g.add(2);
+    let ab1 = g.add_edge(a, b, 10);
+    let ab2 = g.add_edge(a, b, 20);
+    let ca = g.add_edge(c, a, 30);
+    let mut ids = g.edges_connecting(a, b).map(|e| e.id()).collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, vec![ab1, ab2]);
+    assert_eq!(g.edges_connecting(b, a).count(), 0);
+    let incoming = g.edges_directed(a, Direction::Incoming).collect::<Vec<_>>();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!((incoming[0].id(), incoming[0].source(), incoming[0].target()), (ca, c, a));
+    assert_eq!(*incoming[0].weight(), 30);
+    let weights = g.edge_references().map(|e| *e.weight()).collect::<Vec<_>>();
+    assert_eq!(weights, vec![10, 20, 30]);
+
+    let mut u = usgraph(false);
+    let a = u.add(0);
+    let b = u.add(1);
+    u.add_edge(a, b, 1);
+    u.add_edge(b, a, 2);
+    u.add_edge(a, a, 3);
+    let at_a = u.edges_directed(a, Direction::Outgoing).collect::<Vec<_>>();
+    assert_eq!(at_a.len(), 3);
+    assert!(at_a.iter().all(|e| e.source() == a));
+    let mut between = u.edges_connecting(b, a).map(|e| *e.weight()).collect::<Vec<_>>();
+    between.sort();
+    assert_eq!(between, vec![1, 2]);
+    Ok(())
+}
+
+#[test]
+fn csr_and_matrix_agree_with_generated_graphs() -> RecolResult<()> {
+    use std::convert::TryFrom;
+    use rand::{rngs::StdRng, SeedableRng};
+    use recollection::data::generators;
+    let mut rng = StdRng::seed_from_u64(5);
+    for directed in [true, false] {
+        let g: Graph<(), ()> = generators::erdos_renyi(60, 0.1, directed, &mut rng);
+        let csr = Csr::from(&g);
+        let matrix: MatrixGraph<(), ()> = MatrixGraph::try_from(&g)?;
+        for a in g.node_indices() {
+            for b in g.node_indices() {
+                assert_eq!(g.contains_edge(a, b), csr.contains_edge(a, b));
+                assert_eq!(g.contains_edge(a, b), matrix.contains_edge(a, b));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn graph_retain_keeps_order() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    let n = (0..6).map(|i| g.add(i)).collect::<Vec<_>>();
+    for i in 0..5 {
+        g.add_edge(n[i], n[i + 1], i);
+    }
+    g.add_edge(n[5], n[0], 5);
+    g.retain_nodes(|_, &w| w % 3 != 1);
+    let weights = g.node_indices().map(|n| *g.weight(n).unwrap()).collect::<Vec<_>>();
+    assert_eq!(weights, vec![0, 2, 3, 5]);
+    let edges = g.edge_references()
+        .map(|e| (e.source().index(), e.target().index(), *e.weight()))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 2, 2), (3, 0, 5)]);
+
+    g.retain_edges(|_, &w| w != 2);
+    assert_eq!(g.edge_count(), 1);
+    assert!(g.contains_edge(NodeIx::new(3), NodeIx::new(0)));
+    assert_eq!(g.neighbors(NodeIx::new(1)).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn graph_map_and_filter_map() -> RecolResult<()> {
+    let mut g = sgraph(true);
+    let a = g.add("a");
+    let b = g.add("bb");
+    let c = g.add("ccc");
+    g.add_edge(a, b, "ab");
+    g.add_edge(b, c, "bc");
+    g.add_edge(c, a, "ca");
+
+    let lengths = g.map(|_, w| w.len(), |e, _| e.index());
+    assert_eq!(lengths.weight(c), Some(&3));
+    assert_eq!(lengths.edge_weight(EdgeIx::new(1)), Some(&1));
+    assert_eq!(lengths.neighbors(b).collect::<Vec<_>>(), vec![c]);
+
+    let filtered = g.filter_map(
+        |_, w| (w.len() > 1).then(|| w.to_string()),
+        |_, w| (*w != "ca").then_some(w.len()));
+    assert_eq!(filtered.node_count(), 2);
+    assert_eq!(filtered.weight(NodeIx::new(0)).map(String::as_str), Some("bb"));
+    assert_eq!(filtered.edge_count(), 1);
+    assert!(filtered.contains_edge(NodeIx::new(0), NodeIx::new(1)));
+    Ok(())
+}
+
+#[test]
+fn graph_reverse_and_extend() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    g.extend_with_edges(vec![
+        (NodeIx::new(0), NodeIx::new(1), 1),
+        (NodeIx::new(1), NodeIx::new(3), 2),
+        (NodeIx::new(3), NodeIx::new(3), 3),
+    ]);
+    assert_eq!(g.node_count(), 4);
+    assert_eq!(g.weight(NodeIx::new(2)), Some(&0));
+
+    g.reverse();
+    assert!(g.contains_edge(NodeIx::new(1), NodeIx::new(0)));
+    assert!(!g.contains_edge(NodeIx::new(0), NodeIx::new(1)));
+    assert_eq!(g.edge_endpoints(EdgeIx::new(1)), Some((NodeIx::new(3), NodeIx::new(1))));
+    let mut out = g.neighbors(NodeIx::new(3)).collect::<Vec<_>>();
+    out.sort();
+    assert_eq!(out, vec![NodeIx::new(1), NodeIx::new(3)]);
+    assert_eq!(g.neighbors_directed(NodeIx::new(0), Direction::Incoming).collect::<Vec<_>>(), vec![NodeIx::new(1)]);
+    g.remove_edge(EdgeIx::new(0));
+    assert_eq!(g.neighbors(NodeIx::new(1)).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn graph_parallel_edge_policy() -> RecolResult<()> {
+    let mut g = usgraph(false);
+    let a = g.add(0);
+    let b = g.add(1);
+    g.add_edge(a, b, 1);
+    g.add_edge(b, a, 2);
+    assert_eq!(g.edge_multiplicity(a, b), 2);
+    assert_eq!(g.edge_multiplicity(b, a), 2);
+
+    g.set_parallel_edges(ParallelEdges::Forbid);
+    assert!(matches!(g.try_add_edge(a, b, 3), Err(RecolError::ParallelEdge(x, y)) if (x, y) == (a, b)));
+    let scale = 10;
+    g.set_parallel_edges(ParallelEdges::merge(move |w, new| *w += new * scale));
+    let ab = g.try_add_edge(b, a, 1)?;
+    assert_eq!(g.edge_count(), 2);
+    assert_eq!(Some(ab), g.get_edge(b, a));
+    assert_eq!(g.edge_references().map(|e| *e.weight()).sum::<usize>(), 13);
+
+    g.set_self_loops(false);
+    assert!(matches!(g.try_add_edge(a, a, 0), Err(RecolError::SelfLoop(n)) if n == a));
+    g.set_self_loops(true);
+    g.add_edge(a, a, 5);
+    assert_eq!(g.edge_multiplicity(a, a), 1);
+
+    // extending stops at the first refused edge, keeping those before
+    g.set_parallel_edges(ParallelEdges::Forbid);
+    let c = NodeIx::new(2);
+    let refused = g.try_extend_with_edges(vec![(b, c, 1), (c, b, 2), (c, c, 3)]);
+    assert!(matches!(refused, Err(RecolError::ParallelEdge(x, y)) if (x, y) == (c, b)));
+    assert_eq!(g.edge_multiplicity(b, c), 1);
+    assert_eq!(g.edge_multiplicity(c, c), 0);
+    Ok(())
+}
+
+#[test]
+fn graph_try_extend_with_edges_refuses_before_growing() {
+    let mut g = Graph::<(), (), u8>::new_directed();
+    let (last, end) = (NodeIx::<u8>::new(254), NodeIx::<u8>::end());
+    assert!(matches!(g.try_extend_with_edges(vec![(last, end, ())]), Err(RecolError::IndexOverflow)));
+    assert!(matches!(g.try_extend_with_edges(vec![(end, end, ())]), Err(RecolError::IndexOverflow)));
+    assert_eq!(g.node_count(), 0);
+    g.set_self_loops(false);
+    assert!(matches!(g.try_extend_with_edges(vec![(last, last, ())]), Err(RecolError::SelfLoop(_))));
+    assert_eq!(g.node_count(), 0);
+    g.extend_with_edges(vec![(NodeIx::new(0), last, ())]);
+    assert_eq!((g.node_count(), g.edge_count()), (255, 1));
+}
+
+#[test]
+fn graph_simple_collapses_parallel_edges() -> RecolResult<()> {
+    let mut g = usgraph(true);
+    let a = g.add(0);
+    let b = g.add(1);
+    g.add_edge(a, b, 1);
+    g.add_edge(b, a, 2);
+    g.add_edge(a, b, 3);
+    g.add_edge(b, b, 4);
+    let s = g.simple(|w, other| *w += *other);
+    assert_eq!(s.node_count(), 2);
+    let edges = s.edge_references()
+        .map(|e| (e.source(), e.target(), *e.weight()))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(a, b, 4), (b, a, 2)]);
+    let mut s = s;
+    assert!(s.try_add_edge(a, b, 0).is_err());
+    assert!(s.try_add_edge(a, a, 0).is_err());
+
+    let mut u = usgraph(false);
+    let a = u.add(0);
+    let b = u.add(1);
+    u.add_edge(a, b, 1);
+    u.add_edge(b, a, 2);
+    let s = u.simple(|w, other| *w = (*w).max(*other));
+    assert_eq!(s.edge_count(), 1);
+    assert_eq!(s.edge_weight(EdgeIx::new(0)), Some(&2));
+    Ok(())
+}